@@ -1,6 +1,8 @@
 //! Defines the command-line interface for the lstr application.
 
 use crate::sort;
+use crate::template::{Field, LineTemplate};
+use crate::utils;
 use clap::{Parser, Subcommand, ValueEnum};
 use std::fmt;
 use std::path::PathBuf;
@@ -9,7 +11,12 @@ use std::path::PathBuf;
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
-#[command(override_usage = "lstr [OPTIONS] [PATH]\n    lstr interactive [OPTIONS] [PATH]")]
+#[command(override_usage = concat!(
+    "lstr [OPTIONS] [PATH]\n",
+    "    lstr interactive [OPTIONS] [PATH]\n",
+    "    lstr diff [OPTIONS] <OLD> <NEW>\n",
+    "    lstr shell-init [OPTIONS] <SHELL>"
+))]
 pub struct Args {
     /// The subcommand to run. If no subcommand is specified, the classic tree view is displayed.
     #[command(subcommand)]
@@ -26,12 +33,16 @@ pub enum Commands {
     /// Start the interactive TUI explorer.
     #[command(visible_alias = "i")]
     Interactive(InteractiveArgs),
+    /// Compare two directory trees, marking added, removed, and changed entries.
+    Diff(DiffArgs),
+    /// Print a shell function that changes to a directory picked in the explorer.
+    ShellInit(ShellInitArgs),
 }
 
 /// Arguments for the classic `view` command.
-#[derive(Parser, Debug, Default)]
+#[derive(Parser, Debug, Default, Clone)]
 pub struct ViewArgs {
-    /// The path to the directory to display. Defaults to the current directory.
+    /// The path to the directory to display. Defaults to `$LSTR_ROOT`, then the current directory.
     #[arg(default_value = ".")]
     pub path: PathBuf,
     /// Specify when to use colorized output.
@@ -40,54 +51,274 @@ pub struct ViewArgs {
     /// Maximum depth to descend in the directory tree.
     #[arg(short = 'L', long)]
     pub level: Option<usize>,
+    /// With --level, note in the summary how many entries lie below the depth limit.
+    #[arg(long, requires = "level")]
+    pub count_hidden: bool,
+    /// Use the deepest level (up to --level) whose tree fits the terminal's height.
+    #[arg(long)]
+    pub fit: bool,
+    /// Dim entries nested deeper than this level (they are still shown).
+    #[arg(long, value_name = "LEVEL")]
+    pub dim_beyond: Option<usize>,
+    /// Highlight names containing this text without filtering. Can be given more than once.
+    #[arg(long, value_name = "TEXT")]
+    pub highlight: Vec<String>,
     /// Display directories only.
     #[arg(short = 'd', long)]
     pub dirs_only: bool,
+    /// Hide entries whose name matches GLOB, e.g. '*.tmp', and the directories this leaves
+    /// empty. Can be given more than once.
+    #[arg(long, value_name = "GLOB")]
+    pub prune_matching: Vec<String>,
+    /// List the files of each directory together on one line, wrapped at the terminal width.
+    #[arg(long)]
+    pub compact_files: bool,
+    /// Print a blank line after each top-level directory's subtree.
+    #[arg(long, visible_alias = "between-dirs-blank-line")]
+    pub section_spacing: bool,
+    /// Clip every line to at most this many columns, marking cut lines with "…".
+    #[arg(long, value_name = "N")]
+    pub max_width: Option<usize>,
+    /// Merge chains of directories that only contain one directory into a single line.
+    #[arg(long)]
+    pub collapse_single_child: bool,
+    /// List directories holding fewer than N files with a summary instead of their contents.
+    #[arg(long, value_name = "N")]
+    pub collapse_under: Option<usize>,
+    /// Mark empty files and directories with "(empty)".
+    #[arg(long)]
+    pub mark_empty: bool,
+    /// Flag entries whose name differs from a sibling's only in case.
+    #[arg(long)]
+    pub warn_case_collisions: bool,
+    /// Show at most this many children per directory, followed by a "... (N more)" marker.
+    #[arg(long, value_name = "N")]
+    pub max_files_per_dir: Option<usize>,
+    /// Show at most N entries at each depth, counted across all directories.
+    #[arg(long, value_name = "N")]
+    pub entry_limit_per_level: Option<usize>,
+    /// Stop the tree once the files shown add up to more than SIZE (e.g. 100M, 2G).
+    /// Only the output is cut, not the scan: the whole tree is still walked and sorted
+    /// first, though files past the cut aren't stat-ed.
+    #[arg(long, value_name = "SIZE", value_parser = utils::parse_size)]
+    pub max_total_size: Option<u64>,
     /// Display the size of files.
     #[arg(short = 's', long)]
     pub size: bool,
+    /// Show the size of directory entries themselves too, as `ls -l` does (not their contents).
+    #[arg(long, requires = "size")]
+    pub show_size_always: bool,
     /// Display file permissions.
     #[arg(short = 'p', long)]
     pub permissions: bool,
+    /// Append a type indicator to names: / dirs, * executables, @ symlinks, | FIFOs, = sockets.
+    #[arg(short = 'F', long)]
+    pub classify: bool,
+    /// Follow symlinks when reading size and permissions.
+    #[arg(short = 'H', long)]
+    pub dereference: bool,
+    /// Print sizes as exact byte counts instead of human-readable units.
+    #[arg(long)]
+    pub bytes: bool,
+    /// Display how long ago each entry was modified in a leading column (e.g. 2h, 3d).
+    #[arg(long)]
+    pub time: bool,
+    /// With -s, add the total to the summary in human-readable units, even with --bytes.
+    #[arg(long)]
+    pub total_human: bool,
+    /// Display a hash of each file's contents using the given algorithm.
+    #[arg(long, value_name = "ALGORITHM")]
+    pub checksum: Option<ChecksumAlgorithm>,
+    /// Count the lines of each text file in a column, with a total in the summary.
+    #[arg(long, visible_alias = "count-lines")]
+    pub loc: bool,
+    /// Color file names on a scale by size, relative to the largest file in the tree.
+    #[arg(long)]
+    pub size_color: bool,
+    /// Print only the summary counts, without the tree. With -s, the total size is added.
+    #[arg(long)]
+    pub summary_only: bool,
+    /// Omit the blank line between the tree and the summary.
+    #[arg(long)]
+    pub no_summary_newline: bool,
+    /// Follow the summary with the number of entries at each depth.
+    #[arg(long)]
+    pub depth_summary: bool,
+    /// Print a table of file counts and sizes per extension instead of the tree.
+    #[arg(long)]
+    pub count_by_extension: bool,
+    /// List files in sections by extension, each with a count, instead of the tree.
+    #[arg(long, conflicts_with = "count_by_extension")]
+    pub group_by_extension: bool,
+    /// List entries in sections by the first letter of their name instead of the tree.
+    #[arg(
+        long,
+        visible_alias = "group",
+        conflicts_with_all = ["count_by_extension", "group_by_extension"]
+    )]
+    pub group_by_initial: bool,
+    /// Where to report errors encountered while walking the tree.
+    #[arg(long, value_name = "WHERE", default_value_t = ShowErrors::Stderr)]
+    pub show_errors: ShowErrors,
     /// Show all files, including hidden ones.
     #[arg(short = 'a', long, help = "Show all files, including hidden ones")]
     pub all: bool,
     /// Respect .gitignore and other standard ignore files.
     #[arg(short = 'g', long)]
     pub gitignore: bool,
+    /// Don't descend into directories on other filesystems, like `find -xdev`.
+    #[arg(short = 'x', long, visible_alias = "mount-boundary")]
+    pub one_filesystem: bool,
     /// Show git status for files and directories.
     #[arg(short = 'G', long)]
     pub git_status: bool,
+    /// Where to place the git status marker on each line.
+    #[arg(long, value_name = "POSITION", default_value_t = GitStatusPosition::Left)]
+    pub git_status_position: GitStatusPosition,
+    /// How to treat git submodules when showing git status.
+    #[arg(long, value_name = "MODE", default_value_t = GitSubmodules::Ignore)]
+    pub git_submodules: GitSubmodules,
+    /// Show only files changed since this git revision, with their change markers.
+    #[arg(long, value_name = "REV")]
+    pub since_commit: Option<String>,
+    /// Show only files with uncommitted changes, plus the directories leading to them.
+    #[arg(long)]
+    pub git_only_changed: bool,
     /// Display file-specific icons (requires a Nerd Font).
-    #[arg(long, help = "Display file-specific icons (requires a Nerd Font)")]
-    pub icons: bool,
+    #[arg(
+        long,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "all",
+        help = "Display file-specific icons (requires a Nerd Font); --icons=dirs for folders only"
+    )]
+    pub icons: Option<IconMode>,
+    /// Leave out the icons of files with these extensions, e.g. "rs,toml".
+    #[arg(long, value_name = "EXTS", value_delimiter = ',', value_parser = parse_extension)]
+    pub no_icons_for: Vec<String>,
     /// Render file paths as clickable hyperlinks.
     #[arg(long)]
     pub hyperlinks: bool,
-    /// Sort entries by the specified criteria.
+    /// Print the full path of each entry instead of just its name.
+    #[arg(short = 'f', long)]
+    pub full_path: bool,
+    /// With --full-path, shorten paths to N columns by abbreviating their middle directories.
+    #[arg(long, value_name = "N", requires = "full_path")]
+    pub path_shorten: Option<usize>,
+    /// Compose each line from a template such as "{perms} {size:>8} {git} {name}".
+    #[arg(long, value_name = "TEMPLATE", value_parser = LineTemplate::parse)]
+    pub format: Option<LineTemplate>,
+    /// Print only these fields for each entry, e.g. "perms,size,path", as flat delimited lines.
+    #[arg(
+        long,
+        value_name = "FIELDS",
+        value_delimiter = ',',
+        value_parser = Field::parse,
+        conflicts_with = "format"
+    )]
+    pub fields: Vec<Field>,
+    /// The text placed between fields with --fields.
+    #[arg(long, value_name = "STR", default_value = "\t", requires = "fields")]
+    pub separator: String,
+    /// Write the --fields as tab-separated values, or the tree in `tree -J`'s JSON format.
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value_t = OutputFormat::Lines,
+        requires_if("tsv", "fields")
+    )]
+    pub output: OutputFormat,
+    /// Leave out the header row of --output tsv.
+    #[arg(long)]
+    pub no_header: bool,
+    /// Fail on values with tabs or line breaks in --output tsv instead of escaping them.
+    #[arg(long)]
+    pub strict: bool,
+    /// Text to print as the root header instead of the root's absolute path.
+    #[arg(long, value_name = "TEXT")]
+    pub root_label: Option<String>,
+    /// Display paths relative to this directory (paths outside it stay absolute).
+    #[arg(long, value_name = "DIR")]
+    pub relative_to: Option<PathBuf>,
+    /// Show the path as given along with the real path it resolves to in the header.
+    #[arg(long)]
+    pub real_path: bool,
+    /// Show fully resolved paths: the root's, each symlink's target, and with --full-path
+    /// every entry's.
+    #[arg(long, conflicts_with_all = ["relative_to", "real_path"])]
+    pub canonical: bool,
+    /// Sort entries by the specified criteria. Names sort case-insensitively by default.
     #[arg(long, default_value_t = SortType::Name)]
     pub sort: SortType,
+    /// Sort the files within each directory by this instead, keeping directories first.
+    #[arg(long, value_name = "SORT")]
+    pub file_sort: Option<FileSortType>,
+    /// Always sort directories by name, whatever --sort is, keeping them before files.
+    #[arg(long, visible_alias = "sort-folders-by-name-always")]
+    pub dirs_by_name: bool,
     /// Sort directories before files.
     #[arg(long)]
     pub dirs_first: bool,
-    /// Use case-sensitive sorting.
-    #[arg(long)]
-    pub case_sensitive: bool,
+    /// Sort directories after files.
+    #[arg(long, visible_alias = "sort-dirs-last", conflicts_with = "dirs_first")]
+    pub dirs_last: bool,
+    /// Sort names case-sensitively (digits, then uppercase, then lowercase).
+    #[arg(long, visible_alias = "case-sensitive")]
+    pub sort_case_sensitive: bool,
     /// Use natural/version sorting (e.g., file1 < file10).
     #[arg(long)]
     pub natural_sort: bool,
     /// Reverse the sort order.
     #[arg(short = 'r', long)]
     pub reverse: bool,
+    /// Reverse the order of directories only, keeping them before files.
+    #[arg(long)]
+    pub reverse_dirs: bool,
+    /// Reverse the order of files only, keeping directories before them.
+    #[arg(long)]
+    pub reverse_files: bool,
+    /// Reverse the sorted entries of each directory, ties included, keeping the tree's shape.
+    #[arg(long)]
+    pub reverse_within_dirs_only: bool,
     /// Sort dotfiles and dotfolders first.
     #[arg(long)]
     pub dotfiles_first: bool,
+    /// Redraw the tree whenever files under the root change.
+    #[arg(long)]
+    pub watch: bool,
+    /// Milliseconds to wait for changes to settle before redrawing in watch mode.
+    #[arg(long, value_name = "MS", default_value_t = 200)]
+    pub debounce: u64,
+    /// Redraw the tree every SECONDS seconds until interrupted, like `watch lstr`.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with = "watch"
+    )]
+    pub repeat: Option<u64>,
+    /// Exit with status 1 when no entries are listed, like grep.
+    #[arg(long)]
+    pub exit_code: bool,
+    /// Open the interactive viewer instead when no entries would be listed.
+    #[arg(long)]
+    pub interactive_on_empty: bool,
+    /// Show the output in $PAGER (`less -R` by default) when writing to a terminal.
+    #[arg(long)]
+    pub paginate: bool,
+    /// Write the output to this file instead of stdout, without colors unless --color always.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["paginate", "watch", "repeat"])]
+    pub output_file: Option<PathBuf>,
+    /// Print to stderr how long the walk, git status, sorting and rendering each took.
+    #[arg(long, conflicts_with_all = ["watch", "repeat"])]
+    pub profile: bool,
 }
 
 /// Arguments for the `interactive` command.
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 pub struct InteractiveArgs {
-    /// The path to the directory to explore. Defaults to the current directory.
+    /// The path to the directory to explore. Defaults to `$LSTR_ROOT`, then the current directory.
     #[arg(default_value = ".")]
     pub path: PathBuf,
     /// Show all files, including hidden ones.
@@ -96,39 +327,150 @@ pub struct InteractiveArgs {
     /// Respect .gitignore and other standard ignore files.
     #[arg(short = 'g', long)]
     pub gitignore: bool,
+    /// Don't descend into directories on other filesystems, like `find -xdev`.
+    #[arg(short = 'x', long, visible_alias = "mount-boundary")]
+    pub one_filesystem: bool,
     /// Show git status for files and directories.
     #[arg(short = 'G', long)]
     pub git_status: bool,
-    /// Display file-specific icons (requires a Nerd Font).
-    #[arg(long)]
-    pub icons: bool,
+    /// How to treat git submodules when showing git status.
+    #[arg(long, value_name = "MODE", default_value_t = GitSubmodules::Ignore)]
+    pub git_submodules: GitSubmodules,
+    /// Display file-specific icons (requires a Nerd Font); --icons=dirs for folders only.
+    #[arg(
+        long,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "all"
+    )]
+    pub icons: Option<IconMode>,
+    /// Leave out the icons of files with these extensions, e.g. "rs,toml".
+    #[arg(long, value_name = "EXTS", value_delimiter = ',', value_parser = parse_extension)]
+    pub no_icons_for: Vec<String>,
+    /// Display directories only.
+    #[arg(short = 'd', long)]
+    pub dirs_only: bool,
     /// Display the size of files.
     #[arg(short = 's', long)]
     pub size: bool,
     /// Display file permissions.
     #[arg(short = 'p', long)]
     pub permissions: bool,
+    /// Append a type indicator to names: / dirs, * executables, @ symlinks, | FIFOs, = sockets.
+    #[arg(short = 'F', long)]
+    pub classify: bool,
+    /// Follow symlinks when reading size and permissions.
+    #[arg(short = 'H', long)]
+    pub dereference: bool,
+    /// Print sizes as exact byte counts instead of human-readable units.
+    #[arg(long)]
+    pub bytes: bool,
+    /// Display how long ago each entry was modified (e.g. 2h, 3d).
+    #[arg(long)]
+    pub time: bool,
     /// Initial depth to expand the directory tree.
     #[arg(long, value_name = "LEVEL")]
     pub expand_level: Option<usize>,
-    /// Sort entries by the specified criteria.
+    /// Expand the directories whose path below the root matches GLOB, e.g. '**/src'.
+    /// As in gitignore files, '*' stops at a '/' while '**' crosses directories.
+    #[arg(long, value_name = "GLOB")]
+    pub expand_match: Vec<String>,
+    /// Enter directories with →/l and go back with ←/h instead of expanding in place.
+    /// Going back stops at the directory lstr was started in.
+    #[arg(long)]
+    pub navigate: bool,
+    /// Open a directory with Enter as a tree of its own, returning to the outer one with Backspace.
+    #[arg(long)]
+    pub focus: bool,
+    /// Scan each directory when it is first expanded instead of the whole tree at startup.
+    #[arg(long)]
+    pub lazy: bool,
+    /// Keep the selected entry vertically centered while navigating.
+    #[arg(long)]
+    pub follow_selected: bool,
+    /// Print the selected path to stdout when quitting.
+    #[arg(long)]
+    pub print_on_exit: bool,
+    /// Sort entries by the specified criteria. Names sort case-insensitively by default.
     #[arg(long, default_value_t = SortType::Name)]
     pub sort: SortType,
+    /// Sort the files within each directory by this instead, keeping directories first.
+    #[arg(long, value_name = "SORT")]
+    pub file_sort: Option<FileSortType>,
+    /// Always sort directories by name, whatever --sort is, keeping them before files.
+    #[arg(long, visible_alias = "sort-folders-by-name-always")]
+    pub dirs_by_name: bool,
     /// Sort directories before files.
     #[arg(long)]
     pub dirs_first: bool,
-    /// Use case-sensitive sorting.
-    #[arg(long)]
-    pub case_sensitive: bool,
+    /// Sort directories after files.
+    #[arg(long, visible_alias = "sort-dirs-last", conflicts_with = "dirs_first")]
+    pub dirs_last: bool,
+    /// Sort names case-sensitively (digits, then uppercase, then lowercase).
+    #[arg(long, visible_alias = "case-sensitive")]
+    pub sort_case_sensitive: bool,
     /// Use natural/version sorting (e.g., file1 < file10).
     #[arg(long)]
     pub natural_sort: bool,
     /// Reverse the sort order.
     #[arg(short = 'r', long)]
     pub reverse: bool,
+    /// Reverse the order of directories only, keeping them before files.
+    #[arg(long)]
+    pub reverse_dirs: bool,
+    /// Reverse the order of files only, keeping directories before them.
+    #[arg(long)]
+    pub reverse_files: bool,
+    /// Reverse the sorted entries of each directory, ties included, keeping the tree's shape.
+    #[arg(long)]
+    pub reverse_within_dirs_only: bool,
     /// Sort dotfiles and dotfolders first.
     #[arg(long)]
     pub dotfiles_first: bool,
+    /// Redraw the tree whenever files under the root change.
+    #[arg(long)]
+    pub watch: bool,
+    /// Milliseconds to wait for changes to settle before redrawing in watch mode.
+    #[arg(long, value_name = "MS", default_value_t = 200)]
+    pub debounce: u64,
+    /// The colors of the interface, for the terminal's background.
+    #[arg(long, value_name = "NAME", default_value_t = Theme::Default)]
+    pub theme: Theme,
+}
+
+/// Arguments for the `diff` command.
+#[derive(Parser, Debug)]
+pub struct DiffArgs {
+    /// The original directory.
+    pub old: PathBuf,
+    /// The directory to compare against it.
+    pub new: PathBuf,
+    /// Specify when to use colorized output.
+    #[arg(long, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+    /// Maximum depth to descend in the directory trees.
+    #[arg(short = 'L', long)]
+    pub level: Option<usize>,
+    /// Show all files, including hidden ones.
+    #[arg(short = 'a', long)]
+    pub all: bool,
+    /// Respect .gitignore and other standard ignore files.
+    #[arg(short = 'g', long)]
+    pub gitignore: bool,
+    /// Mark files present in both trees as changed when their sizes differ.
+    #[arg(short = 's', long)]
+    pub size: bool,
+}
+
+/// Arguments for the `shell-init` command.
+#[derive(Parser, Debug)]
+pub struct ShellInitArgs {
+    /// The shell to print the function for, e.g. `eval "$(lstr shell-init bash)"`.
+    pub shell: Shell,
+    /// The name of the function.
+    #[arg(long, value_name = "NAME", default_value = "lcd", value_parser = parse_function_name)]
+    pub cmd: String,
 }
 
 /// Defines the available sorting strategies.
@@ -141,8 +483,14 @@ pub enum SortType {
     Size,
     /// Sort by modification time
     Modified,
+    /// Sort by creation time, newest first
+    Created,
     /// Sort by file extension
     Extension,
+    /// Sort by number of immediate children, most first
+    ChildCount,
+    /// Keep the order the filesystem lists entries in
+    None,
 }
 
 /// Defines the choices for the --color option.
@@ -154,27 +502,253 @@ pub enum ColorChoice {
     Never,
 }
 
+/// Defines the choices for the --git-status-position option.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum GitStatusPosition {
+    /// At the far left of the line, before the tree connectors
+    #[default]
+    Left,
+    /// Immediately before the entry name, after the tree connector
+    Name,
+}
+
+/// Defines the choices for the --git-submodules option.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum GitSubmodules {
+    /// Show each submodule as a single entry marked `S`, without descending into it
+    #[default]
+    Ignore,
+    /// Descend into submodules and show the status of their files
+    Show,
+}
+
+/// Defines the choices for the --checksum option.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ChecksumAlgorithm {
+    /// MD5 (fast, not collision resistant)
+    Md5,
+    /// SHA-256
+    #[default]
+    Sha256,
+}
+
+/// Parses a shell function name for `shell-init --cmd`, which is pasted into shell code.
+fn parse_function_name(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('-')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err("expected letters, digits, '_', or '-'".to_string())
+    }
+}
+
+/// Parses an extension for --no-icons-for, accepting `rs` or `.rs` in any case.
+fn parse_extension(extension: &str) -> Result<String, String> {
+    let extension = extension.trim().trim_start_matches('.');
+    if extension.is_empty() {
+        return Err("expected a file extension such as 'rs'".to_string());
+    }
+    Ok(extension.to_lowercase())
+}
+
+/// Defines the choices for the --icons option.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum IconMode {
+    /// Icons for every entry
+    #[default]
+    All,
+    /// Icons for directories only, for fonts that have folder glyphs but few others
+    Dirs,
+}
+
+/// Defines the choices for the --file-sort option.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum FileSortType {
+    /// Sort files by name
+    #[default]
+    Name,
+    /// Sort files by size, largest first
+    Size,
+    /// Sort files by modification time, newest first
+    Modified,
+    /// Sort files by extension
+    Extension,
+}
+
+/// Defines the choices for the --output option.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Join the --fields with --separator, as they are
+    #[default]
+    Lines,
+    /// Tab-separated values with a header row; tabs, line breaks, and backslashes in
+    /// values are escaped as \t, \n, \r, and \\
+    Tsv,
+    /// The JSON of `tree -J`: nested type/name/contents objects and a closing report
+    TreeJson,
+}
+
+/// Defines the choices for the --theme option.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Theme {
+    /// Colors for dark backgrounds
+    #[default]
+    Default,
+    /// Darker colors that stay readable on light backgrounds
+    Light,
+    /// Bright colors only, with no dimmed text
+    HighContrast,
+    /// The Solarized palette
+    Solarized,
+}
+
+/// Defines the shells that `shell-init` supports.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Shell {
+    /// A function for ~/.bashrc
+    Bash,
+    /// A function for ~/.zshrc
+    Zsh,
+    /// A function for ~/.config/fish/config.fish
+    Fish,
+}
+
+/// Defines the choices for the --show-errors option.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ShowErrors {
+    /// Print errors to stderr
+    #[default]
+    Stderr,
+    /// Render errors next to the affected entries in the tree
+    Inline,
+}
+
 impl From<SortType> for sort::SortType {
     fn from(sort_type: SortType) -> Self {
         match sort_type {
             SortType::Name => sort::SortType::Name,
             SortType::Size => sort::SortType::Size,
             SortType::Modified => sort::SortType::Modified,
+            SortType::Created => sort::SortType::Created,
             SortType::Extension => sort::SortType::Extension,
+            SortType::ChildCount => sort::SortType::ChildCount,
+            SortType::None => sort::SortType::None,
+        }
+    }
+}
+
+impl From<FileSortType> for sort::FileSortType {
+    fn from(file_sort: FileSortType) -> Self {
+        match file_sort {
+            FileSortType::Name => sort::FileSortType::Name,
+            FileSortType::Size => sort::FileSortType::Size,
+            FileSortType::Modified => sort::FileSortType::Modified,
+            FileSortType::Extension => sort::FileSortType::Extension,
         }
     }
 }
 
 impl ViewArgs {
+    /// Returns whether the tree itself (header and entries) is printed.
+    pub fn prints_tree(&self) -> bool {
+        !self.summary_only
+            && !self.count_by_extension
+            && !self.group_by_extension
+            && !self.group_by_initial
+            && self.fields.is_empty()
+    }
+
+    /// Returns whether git status markers are shown, which --since-commit and
+    /// --git-only-changed imply.
+    pub fn shows_git_status(&self) -> bool {
+        self.git_status
+            || self.since_commit.is_some()
+            || self.git_only_changed
+            || self.format_uses(Field::Git)
+    }
+
+    /// Returns whether `field` is shown through a --format placeholder or --fields.
+    pub fn format_uses(&self, field: Field) -> bool {
+        self.format.as_ref().is_some_and(|template| template.uses(field))
+            || self.fields.contains(&field)
+    }
+
+    /// Returns whether the entries' metadata is read, for sorting, a size limit, or a
+    /// column that shows it. --max-total-size reads its own, only as far as the cut.
+    pub fn reads_metadata(&self) -> bool {
+        self.size
+            || self.permissions
+            || self.time
+            || self.size_color
+            || self.mark_empty
+            || self.classify
+            || self.collapse_under.is_some()
+            || self.format_uses(Field::Size)
+            || self.format_uses(Field::Perms)
+            || self.format_uses(Field::Time)
+            || self.to_sort_options().reads_metadata()
+    }
+
     /// Creates a SortOptions instance from the ViewArgs.
     pub fn to_sort_options(&self) -> sort::SortOptions {
         sort::SortOptions {
             sort_type: self.sort.into(),
             directories_first: self.dirs_first,
-            case_sensitive: self.case_sensitive,
+            directories_last: self.dirs_last,
+            case_sensitive: self.sort_case_sensitive,
             natural_sort: self.natural_sort,
             reverse: self.reverse,
+            reverse_dirs: self.reverse_dirs,
+            reverse_files: self.reverse_files,
+            reverse_siblings: self.reverse_within_dirs_only,
             dotfiles_first: self.dotfiles_first,
+            file_sort: self.file_sort.map(Into::into),
+            dirs_by_name: self.dirs_by_name,
+        }
+    }
+
+    /// Creates the InteractiveArgs for opening the same tree in the interactive viewer.
+    pub fn to_interactive_args(&self) -> InteractiveArgs {
+        InteractiveArgs {
+            path: self.path.clone(),
+            all: self.all,
+            gitignore: self.gitignore,
+            one_filesystem: self.one_filesystem,
+            git_status: self.shows_git_status(),
+            git_submodules: self.git_submodules,
+            icons: self.icons,
+            no_icons_for: self.no_icons_for.clone(),
+            dirs_only: self.dirs_only,
+            size: self.size,
+            permissions: self.permissions,
+            classify: self.classify,
+            dereference: self.dereference,
+            bytes: self.bytes,
+            time: self.time,
+            expand_level: None,
+            expand_match: Vec::new(),
+            navigate: false,
+            focus: false,
+            lazy: false,
+            follow_selected: false,
+            print_on_exit: false,
+            sort: self.sort,
+            file_sort: self.file_sort,
+            dirs_by_name: self.dirs_by_name,
+            dirs_first: self.dirs_first,
+            dirs_last: self.dirs_last,
+            sort_case_sensitive: self.sort_case_sensitive,
+            natural_sort: self.natural_sort,
+            reverse: self.reverse,
+            reverse_dirs: self.reverse_dirs,
+            reverse_files: self.reverse_files,
+            reverse_within_dirs_only: self.reverse_within_dirs_only,
+            dotfiles_first: self.dotfiles_first,
+            watch: self.watch,
+            debounce: self.debounce,
+            theme: Theme::default(),
         }
     }
 }
@@ -185,10 +759,16 @@ impl InteractiveArgs {
         sort::SortOptions {
             sort_type: self.sort.into(),
             directories_first: self.dirs_first,
-            case_sensitive: self.case_sensitive,
+            directories_last: self.dirs_last,
+            case_sensitive: self.sort_case_sensitive,
             natural_sort: self.natural_sort,
             reverse: self.reverse,
+            reverse_dirs: self.reverse_dirs,
+            reverse_files: self.reverse_files,
+            reverse_siblings: self.reverse_within_dirs_only,
             dotfiles_first: self.dotfiles_first,
+            file_sort: self.file_sort.map(Into::into),
+            dirs_by_name: self.dirs_by_name,
         }
     }
 }
@@ -206,3 +786,59 @@ impl fmt::Display for ColorChoice {
         self.to_possible_value().expect("no values are skipped").get_name().fmt(f)
     }
 }
+
+/// Implements the Display trait for GitStatusPosition to show possible values in help messages.
+impl fmt::Display for GitStatusPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_possible_value().expect("no values are skipped").get_name().fmt(f)
+    }
+}
+
+/// Implements the Display trait for GitSubmodules to show possible values in help messages.
+impl fmt::Display for GitSubmodules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_possible_value().expect("no values are skipped").get_name().fmt(f)
+    }
+}
+
+/// Implements the Display trait for ChecksumAlgorithm to show possible values in help messages.
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_possible_value().expect("no values are skipped").get_name().fmt(f)
+    }
+}
+
+/// Implements the Display trait for FileSortType to show possible values in help messages.
+impl fmt::Display for FileSortType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_possible_value().expect("no values are skipped").get_name().fmt(f)
+    }
+}
+
+/// Implements the Display trait for IconMode to show possible values in help messages.
+impl fmt::Display for IconMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_possible_value().expect("no values are skipped").get_name().fmt(f)
+    }
+}
+
+/// Implements the Display trait for OutputFormat to show possible values in help messages.
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_possible_value().expect("no values are skipped").get_name().fmt(f)
+    }
+}
+
+/// Implements the Display trait for Theme to show possible values in help messages.
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_possible_value().expect("no values are skipped").get_name().fmt(f)
+    }
+}
+
+/// Implements the Display trait for ShowErrors to show possible values in help messages.
+impl fmt::Display for ShowErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_possible_value().expect("no values are skipped").get_name().fmt(f)
+    }
+}
@@ -0,0 +1,90 @@
+//! Computes file content hashes for the `--checksum` column.
+//!
+//! Hashing reads every file in full, so the work is spread across threads and the
+//! results are collected up front, before the tree is rendered.
+
+use crate::app::ChecksumAlgorithm;
+use crate::utils;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// The size of the buffer used to stream file contents into the hasher.
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+impl ChecksumAlgorithm {
+    /// Returns the length of the algorithm's digest in hex characters.
+    pub fn hex_len(self) -> usize {
+        match self {
+            ChecksumAlgorithm::Md5 => 32,
+            ChecksumAlgorithm::Sha256 => 64,
+        }
+    }
+}
+
+/// Hashes the given files in parallel, returning the lowercase hex digest of each.
+///
+/// Files that cannot be read are left out of the map.
+pub fn checksum_files(paths: &[&Path], algorithm: ChecksumAlgorithm) -> HashMap<PathBuf, String> {
+    utils::map_files_parallel(paths, |path| checksum_file(path, algorithm).ok())
+}
+
+/// Hashes a single file with the given algorithm.
+pub fn checksum_file(path: &Path, algorithm: ChecksumAlgorithm) -> io::Result<String> {
+    match algorithm {
+        ChecksumAlgorithm::Md5 => hash_file::<Md5>(path),
+        ChecksumAlgorithm::Sha256 => hash_file::<Sha256>(path),
+    }
+}
+
+/// Streams a file through the hasher `D` in fixed-size chunks.
+fn hash_file<D: Digest>(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_known_digests() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("abc.txt");
+        fs::write(&path, "abc").unwrap();
+
+        assert_eq!(
+            checksum_file(&path, ChecksumAlgorithm::Md5).unwrap(),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(
+            checksum_file(&path, ChecksumAlgorithm::Sha256).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_checksum_files_skips_unreadable() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let present = temp_dir.path().join("present.txt");
+        let missing = temp_dir.path().join("missing.txt");
+        fs::write(&present, "").unwrap();
+
+        let sums = checksum_files(&[present.as_path(), missing.as_path()], ChecksumAlgorithm::Md5);
+        assert_eq!(sums.len(), 1);
+        assert_eq!(sums[&present].len(), ChecksumAlgorithm::Md5.hex_len());
+    }
+}
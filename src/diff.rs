@@ -0,0 +1,244 @@
+//! Implements the `diff` command, which compares two directory trees.
+//!
+//! Both trees are walked with the same filters and merged into a single tree, where
+//! each entry is marked by whether it was added, removed, or changed.
+
+use crate::app::{ColorChoice, DiffArgs};
+use crate::utils;
+use colored::{control, Colorize};
+use ignore::WalkBuilder;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// What is known about an entry in one of the two trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Node {
+    is_dir: bool,
+    /// The file size, only read with --size (0 otherwise)
+    size: u64,
+}
+
+/// How an entry differs between the two trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Change {
+    /// Only in the new tree
+    Added,
+    /// Only in the old tree
+    Removed,
+    /// In both, but the type or (with --size) the file size differs
+    Changed,
+    /// In both and considered the same
+    Unchanged,
+}
+
+/// An entry of the merged tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DiffEntry {
+    /// The path relative to both roots
+    path: PathBuf,
+    is_dir: bool,
+    change: Change,
+    /// The old and new sizes of a file whose size changed
+    sizes: Option<(u64, u64)>,
+}
+
+/// Executes the `diff` command.
+pub fn run(args: &DiffArgs) -> anyhow::Result<()> {
+    for path in [&args.old, &args.new] {
+        if !path.is_dir() {
+            anyhow::bail!("'{}' is not a directory.", path.display());
+        }
+    }
+
+    match args.color {
+        ColorChoice::Always => control::set_override(true),
+        ColorChoice::Never => control::set_override(false),
+        ColorChoice::Auto => {}
+    }
+
+    let old = walk(&args.old, args);
+    let new = walk(&args.new, args);
+    let entries = merge(&old, &new, args.size);
+    _ = print_diff(args, &entries);
+    Ok(())
+}
+
+/// Walks `root` and returns its entries keyed by their path relative to `root`.
+fn walk(root: &Path, args: &DiffArgs) -> BTreeMap<PathBuf, Node> {
+    let mut builder = WalkBuilder::new(root);
+    builder.hidden(!args.all).git_ignore(args.gitignore);
+    builder.add_custom_ignore_filename(utils::LSTR_IGNORE_FILENAME);
+    if let Some(level) = args.level {
+        builder.max_depth(Some(level));
+    }
+
+    let mut nodes = BTreeMap::new();
+    for result in builder.build() {
+        let entry = match result {
+            Ok(entry) => entry,
+            Err(err) => {
+                eprintln!("lstr: ERROR: {err}");
+                continue;
+            }
+        };
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        if relative.as_os_str().is_empty() {
+            continue; // Skip the root directory
+        }
+        let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+        let size = if args.size && !is_dir {
+            utils::entry_metadata(&entry, false).map_or(0, |md| md.len())
+        } else {
+            0
+        };
+        nodes.insert(relative.to_path_buf(), Node { is_dir, size });
+    }
+    nodes
+}
+
+/// Merges both trees into one list in tree order, with the change for each entry.
+///
+/// Paths order component by component, so a directory's contents follow it directly.
+fn merge(
+    old: &BTreeMap<PathBuf, Node>,
+    new: &BTreeMap<PathBuf, Node>,
+    compare_sizes: bool,
+) -> Vec<DiffEntry> {
+    let paths: BTreeSet<&PathBuf> = old.keys().chain(new.keys()).collect();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let (is_dir, change, sizes) = match (old.get(path), new.get(path)) {
+                (Some(before), Some(after)) if before.is_dir != after.is_dir => {
+                    (after.is_dir, Change::Changed, None)
+                }
+                (Some(before), Some(after))
+                    if compare_sizes && !after.is_dir && before.size != after.size =>
+                {
+                    (false, Change::Changed, Some((before.size, after.size)))
+                }
+                (Some(_), Some(after)) => (after.is_dir, Change::Unchanged, None),
+                (None, Some(after)) => (after.is_dir, Change::Added, None),
+                (Some(before), None) => (before.is_dir, Change::Removed, None),
+                (None, None) => return None,
+            };
+            Some(DiffEntry { path: path.clone(), is_dir, change, sizes })
+        })
+        .collect()
+}
+
+/// Builds the tree prefix and connector for each entry, which must be in tree order.
+fn tree_connectors(entries: &[DiffEntry]) -> Vec<String> {
+    // The last child of each directory gets the closing connector.
+    let mut last_child: HashMap<&Path, &Path> = HashMap::new();
+    for entry in entries {
+        last_child.insert(entry.path.parent().unwrap_or(Path::new("")), &entry.path);
+    }
+    let is_last: HashSet<&Path> = last_child.into_values().collect();
+
+    entries
+        .iter()
+        .map(|entry| {
+            let ancestors: Vec<&Path> = entry
+                .path
+                .ancestors()
+                .skip(1)
+                .filter(|ancestor| !ancestor.as_os_str().is_empty())
+                .collect();
+            let mut line = String::new();
+            for ancestor in ancestors.iter().rev() {
+                line.push_str(if is_last.contains(ancestor) { "    " } else { "│   " });
+            }
+            let connector =
+                if is_last.contains(entry.path.as_path()) { "└──" } else { "├──" };
+            line.push_str(connector);
+            line
+        })
+        .collect()
+}
+
+/// Prints the merged tree, followed by a count of each kind of change.
+fn print_diff(args: &DiffArgs, entries: &[DiffEntry]) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    writeln!(
+        out,
+        "{} {} {}",
+        args.old.display().to_string().blue().bold(),
+        "→".dimmed(),
+        args.new.display().to_string().blue().bold()
+    )?;
+
+    let mut counts: HashMap<Change, usize> = HashMap::new();
+    for (entry, tree) in entries.iter().zip(tree_connectors(entries)) {
+        *counts.entry(entry.change).or_insert(0) += 1;
+        let file_name = utils::display_os_str(entry.path.file_name().unwrap_or_default());
+        let name: &str = &file_name;
+        let (marker, name) = match entry.change {
+            Change::Added => ("+".green(), name.green()),
+            Change::Removed => ("-".red(), name.red()),
+            Change::Changed => ("~".yellow(), name.yellow()),
+            Change::Unchanged if entry.is_dir => (" ".normal(), name.blue().bold()),
+            Change::Unchanged => (" ".normal(), name.normal()),
+        };
+        let sizes_str = entry
+            .sizes
+            .map(|(before, after)| {
+                format!(" ({} → {})", utils::format_size(before), utils::format_size(after))
+            })
+            .unwrap_or_default();
+        writeln!(out, "{marker} {tree} {name}{}", sizes_str.dimmed())?;
+    }
+
+    let count = |change| counts.get(&change).copied().unwrap_or(0);
+    writeln!(
+        out,
+        "\n{} added, {} removed, {} changed, {} unchanged",
+        count(Change::Added),
+        count(Change::Removed),
+        count(Change::Changed),
+        count(Change::Unchanged)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(entries: &[(&str, bool, u64)]) -> BTreeMap<PathBuf, Node> {
+        entries
+            .iter()
+            .map(|&(path, is_dir, size)| (PathBuf::from(path), Node { is_dir, size }))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_marks_changes() {
+        let old = tree(&[("src", true, 0), ("src/lib.rs", false, 10), ("old.txt", false, 1)]);
+        let new = tree(&[("src", true, 0), ("src/lib.rs", false, 20), ("new.txt", false, 1)]);
+
+        let changes: Vec<_> =
+            merge(&old, &new, true).into_iter().map(|e| (e.path, e.change)).collect();
+        assert_eq!(
+            changes,
+            vec![
+                (PathBuf::from("new.txt"), Change::Added),
+                (PathBuf::from("old.txt"), Change::Removed),
+                (PathBuf::from("src"), Change::Unchanged),
+                (PathBuf::from("src/lib.rs"), Change::Changed),
+            ]
+        );
+
+        let lib = merge(&old, &new, false).pop().unwrap();
+        assert_eq!(lib.change, Change::Unchanged);
+    }
+
+    #[test]
+    fn test_tree_connectors() {
+        let both = tree(&[("a", true, 0), ("a/x", false, 0), ("a/y", false, 0), ("b", false, 0)]);
+        let entries = merge(&both, &both, false);
+        assert_eq!(tree_connectors(&entries), vec!["├──", "│   ├──", "│   └──", "└──"]);
+    }
+}
@@ -4,7 +4,7 @@
 //! and provide a simplified representation of those statuses for display.
 
 use git2::Repository;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 /// A simplified representation of a file's Git status.
@@ -17,6 +17,8 @@ pub enum FileStatus {
     Typechange,
     Untracked,
     Conflicted,
+    /// A submodule shown as a single opaque entry (its own changes are not inspected).
+    Submodule,
 }
 
 impl FileStatus {
@@ -30,18 +32,73 @@ impl FileStatus {
             Self::Typechange => 'T',
             Self::Untracked => '?',
             Self::Conflicted => 'C',
+            Self::Submodule => 'S',
         }
     }
 }
 
+/// The Git status of a single path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusEntry {
+    pub status: FileStatus,
+    /// For renames, the previous path relative to the repository root.
+    pub renamed_from: Option<PathBuf>,
+}
+
+impl From<FileStatus> for StatusEntry {
+    fn from(status: FileStatus) -> Self {
+        Self { status, renamed_from: None }
+    }
+}
+
 /// A cache mapping file paths to their Git status.
-pub type StatusCache = HashMap<PathBuf, FileStatus>;
+pub type StatusCache = HashMap<PathBuf, StatusEntry>;
 
 /// Contains the status cache and the root path of the repository.
 #[derive(Clone)]
 pub struct GitRepoStatus {
     pub cache: StatusCache,
     pub root: PathBuf,
+    /// Submodule paths (relative to the repository root) that should not be descended into.
+    pub opaque_submodules: Vec<PathBuf>,
+    /// Directories (relative to the repository root) with a changed path somewhere beneath
+    /// them, which are marked even when collapsed.
+    pub dirty_dirs: HashSet<PathBuf>,
+}
+
+impl GitRepoStatus {
+    /// Returns the opaque submodule directories rebased onto `walk_root`, whose
+    /// canonical form is `canonical_root`, so they can be compared against walked paths.
+    pub fn opaque_dirs_under(&self, walk_root: &Path, canonical_root: &Path) -> Vec<PathBuf> {
+        self.opaque_submodules
+            .iter()
+            .filter_map(|sub| self.rebase(sub, walk_root, canonical_root))
+            .collect()
+    }
+
+    /// Returns every path in the cache that lies under the walk root, plus all of its
+    /// ancestor directories, rebased onto `walk_root` like `opaque_dirs_under`.
+    pub fn paths_with_ancestors(
+        &self,
+        walk_root: &Path,
+        canonical_root: &Path,
+    ) -> HashSet<PathBuf> {
+        let mut paths = HashSet::new();
+        for rebased in self.cache.keys().filter_map(|p| self.rebase(p, walk_root, canonical_root)) {
+            for ancestor in rebased.ancestors() {
+                if !paths.insert(ancestor.to_path_buf()) {
+                    break; // The rest of the chain is already present
+                }
+            }
+        }
+        paths
+    }
+
+    /// Maps a repository-relative path to the matching path under `walk_root`.
+    fn rebase(&self, relative: &Path, walk_root: &Path, canonical_root: &Path) -> Option<PathBuf> {
+        let within_root = self.root.join(relative).strip_prefix(canonical_root).ok()?.to_path_buf();
+        Some(walk_root.join(within_root))
+    }
 }
 
 /// Discovers a Git repository from a starting path, scans for file statuses,
@@ -49,7 +106,17 @@ pub struct GitRepoStatus {
 ///
 /// The cache will contain paths relative to the repository root.
 /// If no Git repository is found, it returns `Ok(None)`.
-pub fn load_status(start_path: &Path) -> anyhow::Result<Option<GitRepoStatus>> {
+///
+/// Only the tree under `start_path` is scanned, so a view of one directory in a large
+/// repository doesn't pay for the rest of it.
+///
+/// With `show_submodules`, the statuses of files inside submodules are merged into the
+/// cache, and a dirty submodule directory is reported as modified by the parent repository.
+/// Otherwise each submodule is a single `Submodule` entry, whether or not it is dirty.
+pub fn load_status(
+    start_path: &Path,
+    show_submodules: bool,
+) -> anyhow::Result<Option<GitRepoStatus>> {
     let Ok(repo) = Repository::discover(start_path) else {
         return Ok(None);
     };
@@ -58,9 +125,125 @@ pub fn load_status(start_path: &Path) -> anyhow::Result<Option<GitRepoStatus>> {
         return Ok(None);
     };
 
+    let root = workdir.canonicalize()?;
+    let scope = scope_within(start_path, &root);
+
+    let mut cache = StatusCache::new();
+    let mut opaque_submodules = Vec::new();
+    collect_statuses(&repo, Path::new(""), &scope, &mut cache, show_submodules)?;
+    if !show_submodules {
+        for submodule in repo.submodules()? {
+            let path = submodule.path().to_path_buf();
+            cache.insert(path.clone(), FileStatus::Submodule.into());
+            opaque_submodules.push(path);
+        }
+    }
+
+    // Return the CANONICALIZED workdir path as the root.
+    let dirty_dirs = dirty_dirs(&cache);
+    Ok(Some(GitRepoStatus { cache, root, opaque_submodules, dirty_dirs }))
+}
+
+/// Collects the files that differ between `rev` and the working tree (staged, unstaged,
+/// and untracked changes), keyed relative to the repository root like `load_status`.
+///
+/// Unlike `load_status`, this fails if there is no repository or `rev` is not a commit.
+pub fn load_changes_since(start_path: &Path, rev: &str) -> anyhow::Result<GitRepoStatus> {
+    let Ok(repo) = Repository::discover(start_path) else {
+        anyhow::bail!("--since-commit requires a git repository.");
+    };
+    let Some(workdir) = repo.workdir() else {
+        anyhow::bail!("--since-commit requires a repository with a working tree.");
+    };
+    let Ok(tree) = repo.revparse_single(rev).and_then(|object| object.peel_to_tree()) else {
+        anyhow::bail!("'{rev}' is not a valid git revision.");
+    };
+
+    let root = workdir.canonicalize()?;
+    let scope = scope_within(start_path, &root);
+
+    let mut opts = git2::DiffOptions::new();
+    opts.include_untracked(true).recurse_untracked_dirs(true);
+    if !scope.as_os_str().is_empty() {
+        opts.pathspec(&scope).disable_pathspec_match(true);
+    }
+    let mut diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut opts))?;
+    diff.find_similar(None)?;
+
     let mut cache = StatusCache::new();
+    for delta in diff.deltas() {
+        let status = match delta.status() {
+            git2::Delta::Added | git2::Delta::Copied => FileStatus::New,
+            git2::Delta::Deleted => FileStatus::Deleted,
+            git2::Delta::Modified => FileStatus::Modified,
+            git2::Delta::Renamed => FileStatus::Renamed,
+            git2::Delta::Typechange => FileStatus::Typechange,
+            git2::Delta::Untracked => FileStatus::Untracked,
+            git2::Delta::Conflicted => FileStatus::Conflicted,
+            _ => continue,
+        };
+        let renamed_from = match status {
+            FileStatus::Renamed => delta.old_file().path().map(Path::to_path_buf),
+            _ => None,
+        };
+        if let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) {
+            cache.insert(path.to_path_buf(), StatusEntry { status, renamed_from });
+        }
+    }
+
+    let dirty_dirs = dirty_dirs(&cache);
+    Ok(GitRepoStatus { cache, root, opaque_submodules: Vec::new(), dirty_dirs })
+}
+
+/// Returns where `start_path` lies within the repository at `root`, or an empty path
+/// (the whole repository) if that can't be determined.
+fn scope_within(start_path: &Path, root: &Path) -> PathBuf {
+    start_path
+        .canonicalize()
+        .ok()
+        .and_then(|path| path.strip_prefix(root).ok().map(Path::to_path_buf))
+        .unwrap_or_default()
+}
+
+/// Propagates every change up to the directories above it. Opaque submodule entries are
+/// not changes, since their contents were never inspected.
+fn dirty_dirs(cache: &StatusCache) -> HashSet<PathBuf> {
+    let mut dirs = HashSet::new();
+    for (path, entry) in cache {
+        if entry.status == FileStatus::Submodule {
+            continue;
+        }
+        for dir in path.ancestors().skip(1) {
+            if dir.as_os_str().is_empty() || !dirs.insert(dir.to_path_buf()) {
+                break; // Reached the repository root, or the rest of the chain is present
+            }
+        }
+    }
+    dirs
+}
+
+/// Adds the statuses of `repo` to `cache`, keyed by `prefix` joined with each path.
+///
+/// Only paths under `scope`, relative to the repository, are scanned; an empty `scope`
+/// covers all of it. When `recurse_submodules` is set, checked-out submodules within the
+/// scope are scanned the same way.
+fn collect_statuses(
+    repo: &Repository,
+    prefix: &Path,
+    scope: &Path,
+    cache: &mut StatusCache,
+    recurse_submodules: bool,
+) -> anyhow::Result<()> {
     let mut opts = git2::StatusOptions::new();
-    opts.include_untracked(true).include_ignored(false).recurse_untracked_dirs(true);
+    opts.include_untracked(true)
+        .include_ignored(false)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .exclude_submodules(!recurse_submodules);
+    if !scope.as_os_str().is_empty() {
+        // A literal path, so names with glob characters aren't treated as patterns.
+        opts.pathspec(scope).disable_pathspec_match(true);
+    }
 
     let statuses = repo.statuses(Some(&mut opts))?;
 
@@ -69,14 +252,36 @@ pub fn load_status(start_path: &Path) -> anyhow::Result<Option<GitRepoStatus>> {
             continue;
         };
 
-        if let Some(path_str) = entry.path() {
+        // `path()` is the old path of a staged rename, so renames are keyed by the new one.
+        let rename = entry.head_to_index().filter(|delta| delta.status() == git2::Delta::Renamed);
+        let (path, renamed_from) = match &rename {
+            Some(delta) => (delta.new_file().path(), delta.old_file().path()),
+            None => (entry.path().map(Path::new), None),
+        };
+        if let Some(path) = path {
             // Use the relative path directly as the key.
-            cache.insert(PathBuf::from(path_str), status);
+            let renamed_from = renamed_from.map(|old| prefix.join(old));
+            cache.insert(prefix.join(path), StatusEntry { status, renamed_from });
         }
     }
 
-    // Return the CANONICALIZED workdir path as the root.
-    Ok(Some(GitRepoStatus { cache, root: workdir.canonicalize()? }))
+    if recurse_submodules {
+        for submodule in repo.submodules()? {
+            let sub_path = submodule.path();
+            // The scope either reaches into the submodule or covers all of it.
+            let sub_scope = match scope.strip_prefix(sub_path) {
+                Ok(inner) => inner,
+                Err(_) if sub_path.starts_with(scope) => Path::new(""),
+                Err(_) => continue,
+            };
+            // Submodules that are not checked out have nothing to scan.
+            if let Ok(sub_repo) = submodule.open() {
+                let sub_prefix = prefix.join(sub_path);
+                collect_statuses(&sub_repo, &sub_prefix, sub_scope, cache, true)?;
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Converts a `git2::Status` bitflag into our simplified `FileStatus` enum.
@@ -3,9 +3,27 @@
 //! This module is responsible for mapping file paths to appropriate Nerd Font icons
 //! and `colored` crate `Color` enums to enhance the visual output.
 
+use crate::app::IconMode;
 use colored::Color;
 use std::path::Path;
 
+impl IconMode {
+    /// Returns whether an entry gets an icon in this mode.
+    pub fn shows(self, is_dir: bool) -> bool {
+        self == IconMode::All || is_dir
+    }
+}
+
+/// Returns whether a file's extension is one of `excluded`, which holds lowercase
+/// extensions from `--no-icons-for`.
+pub fn is_excluded(path: &Path, excluded: &[String]) -> bool {
+    !excluded.is_empty()
+        && path.extension().is_some_and(|ext| {
+            let ext = ext.to_string_lossy().to_lowercase();
+            excluded.contains(&ext)
+        })
+}
+
 /// Returns a Nerd Font icon and a display color for a given file path.
 ///
 /// The selection logic first checks for special, well-known filenames. If no
@@ -101,4 +119,22 @@ mod tests {
         assert_eq!(icon, "");
         assert_eq!(color, Color::White);
     }
+
+    #[test]
+    fn test_icon_mode_shows() {
+        assert!(IconMode::All.shows(false));
+        assert!(IconMode::All.shows(true));
+        assert!(!IconMode::Dirs.shows(false));
+        assert!(IconMode::Dirs.shows(true));
+    }
+
+    #[test]
+    fn test_is_excluded() {
+        let excluded = vec!["rs".to_string(), "toml".to_string()];
+        assert!(is_excluded(Path::new("src/main.rs"), &excluded));
+        assert!(is_excluded(Path::new("Cargo.TOML"), &excluded));
+        assert!(!is_excluded(Path::new("README.md"), &excluded));
+        assert!(!is_excluded(Path::new("rs"), &excluded));
+        assert!(!is_excluded(Path::new("main.rs"), &[]));
+    }
 }
@@ -0,0 +1,141 @@
+//! Implements `--output tree-json`, the JSON format of `tree -J`.
+//!
+//! The layout follows `tree` line for line: one entry per line, indented by depth, with
+//! each directory's `contents` closed on a line of its own and a final `report` object
+//! holding the counts. Tools that already read `tree`'s output can read this as is.
+
+use crate::app::ViewArgs;
+use crate::utils::{self, FileKind};
+use ignore::DirEntry;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Prints the walked entries, in tree order, as `tree -J` would.
+///
+/// With --size and --permissions, entries carry `tree`'s `size` and `prot` fields, and
+/// walk errors kept for --show-errors inline become an `error` field on their entry.
+/// Returns whether any entries were printed.
+pub fn print_tree_json(
+    entries: &[DirEntry],
+    args: &ViewArgs,
+    walk_errors: &HashMap<PathBuf, String>,
+) -> io::Result<bool> {
+    let root = json_string(&utils::display_os_str(args.path.as_os_str()));
+    let mut out = format!("[\n  {{\"type\":\"directory\",\"name\":{root},\"contents\":[");
+    // Depths of the directories whose contents are still open, the root's being 0.
+    let mut open_dirs = vec![0];
+    let mut first_in_dir = true;
+    let (mut dir_count, mut file_count) = (0, 0);
+
+    for entry in entries {
+        let depth = entry.depth();
+        while let Some(&open) = open_dirs.last().filter(|&&open| open >= depth) {
+            open_dirs.pop();
+            _ = write!(out, "\n{}]}}", indent(open));
+            first_in_dir = false;
+        }
+        out.push_str(if first_in_dir { "\n" } else { ",\n" });
+
+        let kind = entry.file_type().map_or(FileKind::File, FileKind::of);
+        let name = json_string(&utils::display_os_str(entry.file_name()));
+        _ = write!(out, "{}{{\"type\":\"{}\",\"name\":{name}", indent(depth), type_name(kind));
+        if kind == FileKind::Symlink {
+            if let Ok(target) = fs::read_link(entry.path()) {
+                let target = json_string(&utils::display_os_str(target.as_os_str()));
+                _ = write!(out, ",\"target\":{target}");
+            }
+        }
+        if args.size || args.permissions {
+            let metadata = utils::entry_metadata(entry, args.dereference);
+            #[cfg(unix)]
+            if let (true, Some(md)) = (args.permissions, &metadata) {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = md.permissions().mode();
+                let prot = format!("{}{}", kind.type_char(), utils::format_permissions(mode));
+                _ = write!(out, ",\"prot\":\"{prot}\"");
+            }
+            if let (true, Some(md)) = (args.size, &metadata) {
+                _ = write!(out, ",\"size\":{}", md.len());
+            }
+        }
+        if let Some(message) = walk_errors.get(entry.path()) {
+            _ = write!(out, ",\"error\":{}", json_string(message));
+        }
+
+        if kind == FileKind::Directory {
+            dir_count += 1;
+            out.push_str(",\"contents\":[");
+            open_dirs.push(depth);
+            first_in_dir = true;
+        } else {
+            file_count += 1;
+            out.push('}');
+            first_in_dir = false;
+        }
+    }
+    while let Some(open) = open_dirs.pop() {
+        _ = write!(out, "\n{}]}}", indent(open));
+    }
+    _ = write!(
+        out,
+        "\n,\n  {{\"type\":\"report\",\"directories\":{dir_count},\"files\":{file_count}}}\n]"
+    );
+
+    writeln!(io::stdout().lock(), "{out}")?;
+    Ok(!entries.is_empty())
+}
+
+/// The indentation `tree` gives an entry at `depth`, the root being at 0.
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth + 1)
+}
+
+/// The `type` field `tree` uses for each kind of entry.
+fn type_name(kind: FileKind) -> &'static str {
+    match kind {
+        FileKind::File => "file",
+        FileKind::Directory => "directory",
+        FileKind::Symlink => "link",
+        FileKind::Fifo => "fifo",
+        FileKind::Socket => "socket",
+        FileKind::BlockDevice => "block",
+        FileKind::CharDevice => "char",
+    }
+}
+
+/// Quotes `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("plain.txt"), "\"plain.txt\"");
+        assert_eq!(json_string("say \"hi\"\\"), "\"say \\\"hi\\\"\\\\\"");
+        assert_eq!(json_string("a\tb\nc"), "\"a\\tb\\nc\"");
+        assert_eq!(json_string("bell\u{7}"), "\"bell\\u0007\"");
+    }
+}
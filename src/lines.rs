@@ -0,0 +1,71 @@
+//! Counts the lines of text files for the `--loc` column.
+//!
+//! Like checksums, counting reads every file in full, so it runs across threads before
+//! the tree is rendered. Binary files are recognized and left uncounted.
+
+use crate::utils;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// The size of the buffer files are read in. The first chunk is also what the binary
+/// check looks at.
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Counts the lines of the given files in parallel.
+///
+/// Binary files and files that cannot be read are left out of the map.
+pub fn count_lines_in_files(paths: &[&Path]) -> HashMap<PathBuf, u64> {
+    utils::map_files_parallel(paths, |path| count_lines(path).ok().flatten())
+}
+
+/// Counts the lines of a file, or returns `None` if it looks binary because its first
+/// chunk contains a NUL byte. A last line without a trailing newline still counts.
+pub fn count_lines(path: &Path) -> io::Result<Option<u64>> {
+    let mut file = File::open(path)?;
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+    let mut lines = 0;
+    let mut last_byte = None;
+    let mut first_chunk = true;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        let chunk = &buffer[..read];
+        if first_chunk && chunk.contains(&0) {
+            return Ok(None);
+        }
+        first_chunk = false;
+        lines += chunk.iter().filter(|&&byte| byte == b'\n').count() as u64;
+        last_byte = chunk.last().copied();
+    }
+    if last_byte.is_some_and(|byte| byte != b'\n') {
+        lines += 1;
+    }
+    Ok(Some(lines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_count_lines() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cases: [(&str, &[u8], Option<u64>); 5] = [
+            ("empty.txt", b"", Some(0)),
+            ("one.txt", b"one\n", Some(1)),
+            ("unterminated.txt", b"one\ntwo", Some(2)),
+            ("blank_lines.txt", b"\n\n\n", Some(3)),
+            ("binary.bin", b"ELF\0\x01\n", None),
+        ];
+        for (name, contents, expected) in cases {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            assert_eq!(count_lines(&path).unwrap(), expected, "{name}");
+        }
+    }
+}
@@ -5,18 +5,35 @@
 
 // Declare the modules that make up the application.
 mod app;
+mod checksum;
+mod diff;
 mod git;
 mod icons;
+mod json;
+mod lines;
+mod pager;
+mod profile;
+mod report;
+mod shell;
 mod sort;
+mod template;
 mod tui;
 mod utils;
 mod view;
+mod watch;
 
 use app::{Args, Commands};
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
 #[cfg(windows)]
 use colored::control;
 use lscolors::LsColors;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+/// The environment variable consulted for the root path when none is given.
+const ROOT_ENV_VAR: &str = "LSTR_ROOT";
 
 /// The main function and entry point of the application.
 ///
@@ -34,8 +51,11 @@ fn main() -> anyhow::Result<()> {
     #[cfg(windows)]
     let _ = control::set_virtual_terminal(true);
 
-    // Parse the command-line arguments into our Args struct.
-    let args = Args::parse();
+    // Parse the command-line arguments into our Args struct. We keep the raw
+    // matches around so we can tell whether the path was given explicitly.
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    apply_root_from_env(&matches, &mut args);
 
     // Create the LsColors instance from the environment
     let ls_colors = LsColors::from_env().unwrap_or_default();
@@ -43,6 +63,54 @@ fn main() -> anyhow::Result<()> {
     // Check if a subcommand was passed. If not, default to the `view` command.
     match &args.command {
         Some(Commands::Interactive(interactive_args)) => tui::run(interactive_args, &ls_colors),
-        None => view::run(&args.view, &ls_colors),
+        Some(Commands::Diff(diff_args)) => diff::run(diff_args),
+        Some(Commands::ShellInit(shell_args)) => shell::run(shell_args),
+        // Watch mode redraws in place, which a pager cannot follow.
+        None if args.view.paginate
+            && !args.view.watch
+            && args.view.repeat.is_none()
+            && pager::should_page() =>
+        {
+            pager::run_paged(args.view.color)
+        }
+        None => {
+            if let Some(path) =
+                args.view.output_file.as_deref().filter(|_| pager::should_write_file())
+            {
+                return pager::run_into_file(path, args.view.color);
+            }
+            let listed = view::run(&args.view, &ls_colors)?;
+            // Like grep, report that nothing matched through the exit status.
+            if args.view.exit_code && !listed {
+                process::exit(1);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Replaces the default root path with `$LSTR_ROOT` when it is set.
+///
+/// The precedence is: explicit path argument > `$LSTR_ROOT` > current directory.
+/// An empty variable is treated as unset.
+fn apply_root_from_env(matches: &ArgMatches, args: &mut Args) {
+    let Some(root) = env::var_os(ROOT_ENV_VAR).filter(|v| !v.is_empty()) else {
+        return;
+    };
+    let is_defaulted = |m: &ArgMatches| m.value_source("path") == Some(ValueSource::DefaultValue);
+
+    match &mut args.command {
+        Some(Commands::Interactive(interactive_args)) => {
+            if matches.subcommand_matches("interactive").is_some_and(is_defaulted) {
+                interactive_args.path = PathBuf::from(root);
+            }
+        }
+        // Both trees are always given explicitly, and shell-init takes no path.
+        Some(Commands::Diff(_) | Commands::ShellInit(_)) => {}
+        None => {
+            if is_defaulted(matches) {
+                args.view.path = PathBuf::from(root);
+            }
+        }
     }
 }
@@ -0,0 +1,98 @@
+//! Pipes the classic view through the user's pager for `--paginate`, or into a file for
+//! `--output-file`.
+//!
+//! The view writes straight to stdout, so rather than buffering it, lstr runs itself
+//! again with its stdout connected to the pager's stdin or to the file.
+
+use crate::app::ColorChoice;
+use std::env;
+use std::fs::File;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+
+/// Set for the lstr process that renders into the pager, so it doesn't page again.
+const PAGED_ENV_VAR: &str = "LSTR_PAGED";
+
+/// Set for the lstr process that renders into the --output-file, so it doesn't run again.
+const OUTPUT_FILE_ENV_VAR: &str = "LSTR_WRITING_OUTPUT_FILE";
+
+/// The pager used when `$PAGER` is unset; `-R` lets colors through.
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Options for `less` when `$LESS` is unset: pass colors through, quit if the output
+/// fits on one screen, and leave it on the terminal afterwards.
+const DEFAULT_LESS_OPTIONS: &str = "FRX";
+
+/// Returns whether the output should go through a pager: stdout is a terminal and this
+/// process is not already the one writing into a pager.
+pub fn should_page() -> bool {
+    io::stdout().is_terminal() && env::var_os(PAGED_ENV_VAR).is_none()
+}
+
+/// Runs lstr again with the same arguments, its output piped into the pager, and waits
+/// for both to finish. Exits with lstr's status if the inner run failed.
+pub fn run_paged(color: ColorChoice) -> anyhow::Result<()> {
+    let pager = env::var("PAGER").ok().filter(|p| !p.trim().is_empty());
+    let pager = pager.as_deref().unwrap_or(DEFAULT_PAGER);
+    let mut parts = pager.split_whitespace();
+    let Some(program) = parts.next() else {
+        anyhow::bail!("$PAGER is empty.");
+    };
+
+    let mut pager_command = Command::new(program);
+    pager_command.args(parts).stdin(Stdio::piped());
+    if env::var_os("LESS").is_none() {
+        pager_command.env("LESS", DEFAULT_LESS_OPTIONS);
+    }
+    let mut pager_process = match pager_command.spawn() {
+        Ok(process) => process,
+        Err(err) => anyhow::bail!("Failed to start pager '{program}': {err}"),
+    };
+    let Some(pager_stdin) = pager_process.stdin.take() else {
+        anyhow::bail!("Failed to open the pager's input.");
+    };
+
+    let mut lstr_command = Command::new(env::current_exe()?);
+    lstr_command.args(env::args_os().skip(1)).env(PAGED_ENV_VAR, "1").stdout(pager_stdin);
+    // Colors would otherwise be turned off, since the output is no longer a terminal.
+    if color == ColorChoice::Auto {
+        lstr_command.env("CLICOLOR_FORCE", "1");
+    }
+    let status = lstr_command.spawn()?.wait();
+    // Drop our end of the pipe so the pager sees the end of the output.
+    drop(lstr_command);
+    pager_process.wait()?;
+
+    let status = status?;
+    if !status.success() {
+        process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Returns whether the output should be written to the --output-file by another lstr
+/// process, which is the case unless this is that process.
+pub fn should_write_file() -> bool {
+    env::var_os(OUTPUT_FILE_ENV_VAR).is_none()
+}
+
+/// Runs lstr again with the same arguments, its output going to `path`, and waits for it.
+/// Exits with lstr's status if the inner run failed.
+pub fn run_into_file(path: &Path, color: ColorChoice) -> anyhow::Result<()> {
+    let file = match File::create(path) {
+        Ok(file) => file,
+        Err(err) => anyhow::bail!("Failed to create '{}': {err}", path.display()),
+    };
+    let mut lstr_command = Command::new(env::current_exe()?);
+    lstr_command.args(env::args_os().skip(1)).env(OUTPUT_FILE_ENV_VAR, "1").stdout(file);
+    // The file isn't a terminal, so colors are off unless --color always asks for them.
+    if color == ColorChoice::Auto {
+        lstr_command.env_remove("CLICOLOR_FORCE");
+    }
+    let status = lstr_command.status()?;
+    if !status.success() {
+        process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
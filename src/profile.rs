@@ -0,0 +1,60 @@
+//! Implements `--profile`, which reports on stderr how long each phase of a run took.
+//!
+//! Profiling is off unless asked for, in which case no clock is ever read.
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// The times of the phases of a run, in the order they ended.
+pub struct Profile {
+    /// When the current phase started, or `None` when profiling is off
+    phase_start: Option<Instant>,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Profile {
+    pub fn new(enabled: bool) -> Self {
+        Self { phase_start: enabled.then(Instant::now), phases: Vec::new() }
+    }
+
+    /// Ends the current phase, recording its time under `name`, and starts the next one.
+    pub fn phase(&mut self, name: &'static str) {
+        if let Some(start) = self.phase_start.as_mut() {
+            let now = Instant::now();
+            self.phases.push((name, now - *start));
+            *start = now;
+        }
+    }
+
+    /// Ends the last phase as `name`, then prints the time of every phase and the total.
+    pub fn report(mut self, name: &'static str) {
+        if self.phase_start.is_none() {
+            return;
+        }
+        self.phase(name);
+        let total: Duration = self.phases.iter().map(|(_, duration)| *duration).sum();
+        let mut stderr = io::stderr().lock();
+        _ = writeln!(stderr, "lstr: profile:");
+        for (name, duration) in self.phases.iter().chain([("total", total)].iter()) {
+            _ = writeln!(stderr, "  {name:<10} {:>9.2} ms", duration.as_secs_f64() * 1000.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases() {
+        let mut off = Profile::new(false);
+        off.phase("walk");
+        assert!(off.phases.is_empty());
+
+        let mut on = Profile::new(true);
+        on.phase("walk");
+        on.phase("sort");
+        let names: Vec<_> = on.phases.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["walk", "sort"]);
+    }
+}
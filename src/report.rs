@@ -0,0 +1,157 @@
+//! Implements the report-style outputs of the classic view.
+//!
+//! These outputs reuse the same walk and filters as the tree view, but print
+//! aggregated tables instead of the tree itself.
+
+use crate::app::ViewArgs;
+use crate::utils;
+use colored::Colorize;
+use ignore::DirEntry;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+/// The label used for files without an extension.
+const NO_EXTENSION: &str = "(none)";
+
+/// The section for names that don't start with a letter.
+const NON_LETTER_INITIAL: &str = "#";
+
+/// Prints a frequency table of file extensions, with counts and total sizes.
+///
+/// Rows are sorted by count (descending), with ties broken by extension name. Returns
+/// whether any files were counted.
+pub fn print_extension_counts(entries: &[DirEntry], args: &ViewArgs) -> io::Result<bool> {
+    let mut counts: HashMap<String, (usize, u64)> = HashMap::new();
+    for entry in entries {
+        if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+            continue;
+        }
+        let size = utils::entry_metadata(entry, args.dereference).map_or(0, |m| m.len());
+        let slot = counts.entry(extension_label(entry)).or_insert((0, 0));
+        slot.0 += 1;
+        slot.1 += size;
+    }
+
+    let mut rows: Vec<_> = counts.into_iter().collect();
+    rows.sort_by(|(ext_a, (count_a, _)), (ext_b, (count_b, _))| {
+        count_b.cmp(count_a).then_with(|| ext_a.cmp(ext_b))
+    });
+
+    let ext_width = rows.iter().map(|(ext, _)| ext.chars().count()).max().unwrap_or(0);
+    let count_width = rows.iter().map(|(_, (count, _))| count.to_string().len()).max().unwrap_or(0);
+    let file_count: usize = rows.iter().map(|(_, (count, _))| count).sum();
+
+    let mut out = io::stdout().lock();
+    for (extension, (count, size)) in &rows {
+        writeln!(
+            out,
+            "{}  {count:>count_width$}  {}",
+            format!("{extension:<ext_width$}").bold(),
+            utils::display_size(*size, args.bytes).dimmed()
+        )?;
+    }
+    writeln!(out, "\n{} extensions, {file_count} files", rows.len())?;
+    Ok(!rows.is_empty())
+}
+
+/// Prints the files grouped into one section per extension, each headed by the
+/// extension and its file count, instead of the tree.
+///
+/// Sections are ordered by extension, with files that have none last. Within a section,
+/// files keep their tree order and are shown relative to the root. Returns whether any
+/// files were listed.
+pub fn print_extension_groups(entries: &[DirEntry], args: &ViewArgs) -> io::Result<bool> {
+    let mut groups: BTreeMap<String, Vec<&DirEntry>> = BTreeMap::new();
+    let mut no_extension = Vec::new();
+    for entry in entries {
+        if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+            continue;
+        }
+        match entry.path().extension() {
+            Some(_) => groups.entry(extension_label(entry)).or_default().push(entry),
+            None => no_extension.push(entry),
+        }
+    }
+    let no_extension = (!no_extension.is_empty()).then(|| (NO_EXTENSION.to_string(), no_extension));
+    let sections: Vec<_> = groups.into_iter().chain(no_extension).collect();
+
+    let mut out = io::stdout().lock();
+    for (index, (extension, files)) in sections.iter().enumerate() {
+        if index > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{} {}", extension.bold(), format!("({} files)", files.len()).dimmed())?;
+        for entry in files {
+            let path = entry.path().strip_prefix(&args.path).unwrap_or(entry.path());
+            let size_str = if args.size {
+                let size = utils::entry_metadata(entry, args.dereference).map_or(0, |m| m.len());
+                format!(" ({})", utils::display_size(size, args.bytes))
+            } else {
+                String::new()
+            };
+            writeln!(out, "  {}{}", utils::display_os_str(path.as_os_str()), size_str.dimmed())?;
+        }
+    }
+
+    let file_count: usize = sections.iter().map(|(_, files)| files.len()).sum();
+    writeln!(out, "\n{} extensions, {file_count} files", sections.len())?;
+    Ok(file_count > 0)
+}
+
+/// Prints the entries grouped into one section per initial, each headed by the uppercased
+/// letter and its entry count, instead of the tree.
+///
+/// Sections are ordered by letter, with names that start with anything else last under
+/// `#`. Within a section, entries keep their tree order and are shown relative to the
+/// root, directories with a trailing slash. Returns whether any entries were listed.
+pub fn print_initial_groups(entries: &[DirEntry], args: &ViewArgs) -> io::Result<bool> {
+    let mut groups: BTreeMap<String, Vec<&DirEntry>> = BTreeMap::new();
+    let mut non_letters = Vec::new();
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy();
+        match name.chars().next().filter(|c| c.is_alphabetic()) {
+            Some(initial) => {
+                groups.entry(initial.to_uppercase().collect()).or_default().push(entry)
+            }
+            None => non_letters.push(entry),
+        }
+    }
+    let non_letters =
+        (!non_letters.is_empty()).then(|| (NON_LETTER_INITIAL.to_string(), non_letters));
+    let sections: Vec<_> = groups.into_iter().chain(non_letters).collect();
+
+    let mut out = io::stdout().lock();
+    for (index, (initial, group)) in sections.iter().enumerate() {
+        if index > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{} {}", initial.bold(), format!("({} entries)", group.len()).dimmed())?;
+        for entry in group {
+            let path = entry.path().strip_prefix(&args.path).unwrap_or(entry.path());
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            let size_str = if args.size && !is_dir {
+                let size = utils::entry_metadata(entry, args.dereference).map_or(0, |m| m.len());
+                format!(" ({})", utils::display_size(size, args.bytes))
+            } else {
+                String::new()
+            };
+            let slash = if is_dir { "/" } else { "" };
+            let path = utils::display_os_str(path.as_os_str());
+            writeln!(out, "  {path}{slash}{}", size_str.dimmed())?;
+        }
+    }
+
+    let entry_count: usize = sections.iter().map(|(_, group)| group.len()).sum();
+    writeln!(out, "\n{} groups, {entry_count} entries", sections.len())?;
+    Ok(entry_count > 0)
+}
+
+/// Returns the extension an entry is counted under, or [`NO_EXTENSION`]. Extensions are
+/// lowercased, so `photo.JPG` and `photo.jpg` land together under `jpg`.
+fn extension_label(entry: &DirEntry) -> String {
+    entry
+        .path()
+        .extension()
+        .map(|ext| utils::display_os_str(ext).to_lowercase())
+        .unwrap_or_else(|| NO_EXTENSION.to_string())
+}
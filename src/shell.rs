@@ -0,0 +1,38 @@
+//! Implements `lstr shell-init`, which prints a shell function for changing directory.
+//!
+//! The function runs the interactive explorer with `--print-on-exit` and changes to the
+//! selected directory, or to the directory of the selected file. The explorer draws on
+//! stderr when stdout isn't a terminal, so the shell can capture the printed path.
+
+use crate::app::{Shell, ShellInitArgs};
+use std::io::{self, Write};
+
+/// The function for bash and zsh. `NAME` stands for the function's name.
+const POSIX_FUNCTION: &str = r#"NAME() {
+    local target
+    target="$(command lstr interactive --print-on-exit "$@")" || return
+    [ -n "$target" ] || return 0
+    [ -d "$target" ] || target="$(dirname -- "$target")"
+    cd -- "$target"
+}
+"#;
+
+/// The function for fish. `NAME` stands for the function's name.
+const FISH_FUNCTION: &str = r#"function NAME
+    set -l target (command lstr interactive --print-on-exit $argv)
+    or return
+    test -n "$target"; or return 0
+    test -d "$target"; or set target (dirname -- "$target")
+    cd $target
+end
+"#;
+
+/// Prints the function for the requested shell, to be evaluated by its startup file.
+pub fn run(args: &ShellInitArgs) -> anyhow::Result<()> {
+    let template = match args.shell {
+        Shell::Bash | Shell::Zsh => POSIX_FUNCTION,
+        Shell::Fish => FISH_FUNCTION,
+    };
+    io::stdout().write_all(template.replacen("NAME", &args.cmd, 1).as_bytes())?;
+    Ok(())
+}
@@ -3,27 +3,50 @@
 //! This module implements various sorting strategies for file and directory entries,
 //! ensuring consistent behavior across all supported platforms (Windows, macOS, Linux).
 
+use crate::utils::MetadataCache;
 use ignore::DirEntry;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::fs::Metadata;
+use std::path::PathBuf;
+use std::sync::Once;
+use std::time::SystemTime;
+
+/// Ensures the creation-time fallback notice is only printed once per run.
+static CREATED_FALLBACK_NOTICE: Once = Once::new();
 
 /// Defines the available sorting strategies.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SortType {
     /// Sort by name (default)
+    #[default]
     Name,
     /// Sort by file size
     Size,
     /// Sort by modification time
     Modified,
+    /// Sort by creation time, newest first
+    Created,
     /// Sort by file extension
     Extension,
+    /// Sort by number of immediate children, most first
+    ChildCount,
+    /// Keep the order entries were read in, which depends on the filesystem
+    None,
 }
 
-impl Default for SortType {
-    fn default() -> Self {
-        Self::Name
-    }
+/// Defines the strategies for sorting files separately from directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileSortType {
+    /// Sort files by name
+    Name,
+    /// Sort files by size, largest first
+    Size,
+    /// Sort files by modification time, newest first
+    Modified,
+    /// Sort files by extension
+    Extension,
 }
 
 /// Configuration options for sorting directory entries.
@@ -33,14 +56,56 @@ pub struct SortOptions {
     pub sort_type: SortType,
     /// Whether to sort directories before files
     pub directories_first: bool,
+    /// Whether to sort directories after files; `directories_first` wins when both are set
+    pub directories_last: bool,
     /// Whether to use case-sensitive name sorting
     pub case_sensitive: bool,
     /// Whether to use natural/version sorting (e.g., file1 < file10)
     pub natural_sort: bool,
     /// Whether to reverse the sort order
     pub reverse: bool,
+    /// Whether to reverse the order among directories only (groups directories first)
+    pub reverse_dirs: bool,
+    /// Whether to reverse the order among files only (groups directories first)
+    pub reverse_files: bool,
+    /// Whether to reverse each directory's children after sorting, which unlike `reverse`
+    /// also flips entries that compare equal
+    pub reverse_siblings: bool,
     /// Whether to sort dotfiles/dotfolders first
     pub dotfiles_first: bool,
+    /// The strategy for ordering files among themselves instead of `sort_type`
+    /// (groups directories first)
+    pub file_sort: Option<FileSortType>,
+    /// Whether directories are ordered among themselves by name instead of `sort_type`
+    /// (groups directories first)
+    pub dirs_by_name: bool,
+}
+
+impl SortOptions {
+    /// Returns whether sorting compares sizes or times, which come from the metadata.
+    pub fn reads_metadata(&self) -> bool {
+        matches!(self.sort_type, SortType::Size | SortType::Modified | SortType::Created)
+            || matches!(self.file_sort, Some(FileSortType::Size | FileSortType::Modified))
+    }
+
+    /// Returns whether sorting compares child counts, which need the level below the
+    /// deepest one shown to be walked too.
+    pub fn counts_children(&self) -> bool {
+        self.sort_type == SortType::ChildCount
+    }
+}
+
+/// The number of immediate children of each directory, keyed by its path.
+pub type ChildCounts = HashMap<PathBuf, usize>;
+
+/// Counts the immediate children of each directory among `entries`. Only the walked
+/// entries are counted, so hidden and ignored ones are left out as they are in the tree.
+pub fn count_children(entries: &[DirEntry]) -> ChildCounts {
+    let mut counts = ChildCounts::new();
+    for parent in entries.iter().filter_map(|entry| entry.path().parent()) {
+        *counts.entry(parent.to_path_buf()).or_default() += 1;
+    }
+    counts
 }
 
 /// Sorts a vector of directory entries according to the given options.
@@ -53,11 +118,14 @@ pub struct SortOptions {
 ///
 /// * `entries` - A mutable reference to the vector of entries to sort
 /// * `options` - The sorting configuration to apply
+/// * `metadata` - Metadata already read for the entries; the rest is read as needed
+/// * `child_counts` - The child count of each directory, from [`count_children`]
 ///
 /// # Examples
 ///
 /// ```rust
-/// use lstr::sort::{sort_entries, SortOptions, SortType};
+/// use lstr::sort::{sort_entries, ChildCounts, SortOptions, SortType};
+/// use lstr::utils::MetadataCache;
 ///
 /// let mut entries = vec![/* ... */];
 /// let options = SortOptions {
@@ -65,12 +133,17 @@ pub struct SortOptions {
 ///     directories_first: true,
 ///     ..Default::default()
 /// };
-/// sort_entries(&mut entries, &options);
+/// sort_entries(&mut entries, &options, &MetadataCache::new(), &ChildCounts::new());
 /// ```
-pub fn sort_entries(entries: &mut [DirEntry], options: &SortOptions) {
+pub fn sort_entries(
+    entries: &mut [DirEntry],
+    options: &SortOptions,
+    metadata: &MetadataCache,
+    child_counts: &ChildCounts,
+) {
     entries.sort_by(|a, b| {
-        let result = compare_entries(a, b, options);
-        if options.reverse {
+        let result = compare_entries(a, b, options, metadata, child_counts);
+        if is_reversed(a, b, options) {
             result.reverse()
         } else {
             result
@@ -78,14 +151,35 @@ pub fn sort_entries(entries: &mut [DirEntry], options: &SortOptions) {
     });
 }
 
+/// Decides whether the comparison of `a` and `b` is reversed.
+///
+/// `reverse` flips every pair, while `reverse_dirs` and `reverse_files` only flip pairs
+/// within their group. Those per-group flags keep directories grouped before files (see
+/// `compare_entries`), so pairs from different groups are never affected by them.
+fn is_reversed(a: &DirEntry, b: &DirEntry, options: &SortOptions) -> bool {
+    if options.reverse {
+        return true;
+    }
+    let a_is_dir = a.file_type().is_some_and(|ft| ft.is_dir());
+    let b_is_dir = b.file_type().is_some_and(|ft| ft.is_dir());
+    match (a_is_dir, b_is_dir) {
+        (true, true) => options.reverse_dirs,
+        (false, false) => options.reverse_files,
+        _ => false,
+    }
+}
+
 /// Sorts directory entries hierarchically, preserving tree structure.
 /// 
 /// This builds an explicit tree structure and then reconstructs the entries
 /// in depth-first order with proper sibling sorting within each parent directory.
 /// Use this instead of sort_entries() when you need to preserve parent-child relationships.
-pub fn sort_entries_hierarchically(entries: &mut Vec<DirEntry>, options: &SortOptions) {
-    use std::collections::HashMap;
-    
+pub fn sort_entries_hierarchically(
+    entries: &mut Vec<DirEntry>,
+    options: &SortOptions,
+    metadata: &MetadataCache,
+    child_counts: &ChildCounts,
+) {
     if entries.is_empty() {
         return;
     }
@@ -105,7 +199,10 @@ pub fn sort_entries_hierarchically(entries: &mut Vec<DirEntry>, options: &SortOp
     
     // Sort children within each parent directory
     for children in children_map.values_mut() {
-        sort_entries(children, options);
+        sort_entries(children, options, metadata, child_counts);
+        if options.reverse_siblings {
+            children.reverse();
+        }
     }
     
     // Find root entries (depth 1, since we skip depth 0)
@@ -115,7 +212,10 @@ pub fn sort_entries_hierarchically(entries: &mut Vec<DirEntry>, options: &SortOp
         .collect();
     
     // Sort root entries
-    sort_entries(&mut root_entries, options);
+    sort_entries(&mut root_entries, options, metadata, child_counts);
+    if options.reverse_siblings {
+        root_entries.reverse();
+    }
     
     // Recursively collect entries in depth-first order
     fn collect_tree_entries(
@@ -144,53 +244,91 @@ pub fn sort_entries_hierarchically(entries: &mut Vec<DirEntry>, options: &SortOp
 }
 
 /// Compares two directory entries according to the sorting options.
-pub fn compare_entries(a: &DirEntry, b: &DirEntry, options: &SortOptions) -> Ordering {
+pub fn compare_entries(
+    a: &DirEntry,
+    b: &DirEntry,
+    options: &SortOptions,
+    metadata: &MetadataCache,
+    child_counts: &ChildCounts,
+) -> Ordering {
     let a_is_dir = a.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
     let b_is_dir = b.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
     let a_is_dotfile = is_dotfile(a);
     let b_is_dotfile = is_dotfile(b);
 
+    let dirs_last = options.directories_last && !options.directories_first;
+
     // Handle dotfiles-first and directories-first sorting
-    // Order: dotfolders → folders → dotfiles → files
+    // Order: dotfolders → folders → dotfiles → files, or with --dirs-last
+    // dotfiles → files → dotfolders → folders
     if options.dotfiles_first {
-        match (a_is_dotfile, a_is_dir, b_is_dotfile, b_is_dir) {
-            // Same category - continue to name sorting
-            (true, true, true, true) |   // Both dotfolders
-            (false, true, false, true) | // Both regular folders  
-            (true, false, true, false) | // Both dotfiles
-            (false, false, false, false) => {}, // Both regular files
-
-            // Different categories - apply priority order
-            (true, true, _, _) => return Ordering::Less,   // a is dotfolder (highest priority)
-            (_, _, true, true) => return Ordering::Greater, // b is dotfolder
-            (false, true, _, _) => return Ordering::Less,   // a is regular folder
-            (_, _, false, true) => return Ordering::Greater, // b is regular folder
-            (true, false, _, _) => return Ordering::Less,   // a is dotfile
-            (_, _, true, false) => return Ordering::Greater, // b is dotfile
+        // Lower ranks come first: the group, then dotfiles before the rest
+        let rank = |is_dir: bool, is_dotfile: bool| (is_dir == dirs_last, !is_dotfile);
+        match rank(a_is_dir, a_is_dotfile).cmp(&rank(b_is_dir, b_is_dotfile)) {
+            Ordering::Equal => {} // Same category - continue to name sorting
+            ordering => return ordering,
         }
-    } else if options.directories_first {
+    } else if options.directories_first
+        || options.directories_last
+        || options.reverse_dirs
+        || options.reverse_files
+        || options.file_sort.is_some()
+        || options.dirs_by_name
+    {
         // Original directories-first logic (without dotfile priority)
         match (a_is_dir, b_is_dir) {
+            (true, false) if dirs_last => return Ordering::Greater,
+            (false, true) if dirs_last => return Ordering::Less,
             (true, false) => return Ordering::Less,
             (false, true) => return Ordering::Greater,
             _ => {} // Both are dirs or both are files, continue
         }
     }
 
-    // Apply the primary sorting strategy
-    match options.sort_type {
-        SortType::Name => compare_by_name(a, b, options),
-        SortType::Size => compare_by_size(a, b),
-        SortType::Modified => compare_by_modified(a, b),
-        SortType::Extension => compare_by_extension(a, b, options),
+    // Apply the primary sorting strategy, which --file-sort replaces between two files
+    // and --dirs-by-name between two directories
+    let primary = match options.file_sort.filter(|_| !a_is_dir && !b_is_dir) {
+        Some(file_sort) => compare_files(a, b, file_sort, options, metadata),
+        None if options.dirs_by_name && a_is_dir && b_is_dir => compare_by_name(a, b, options),
+        None => match options.sort_type {
+            SortType::Name => compare_by_name(a, b, options),
+            SortType::Size => compare_by_size(a, b, metadata),
+            SortType::Modified => compare_by_modified(a, b, metadata),
+            SortType::Created => compare_by_created(a, b, metadata),
+            SortType::Extension => compare_by_extension(a, b, options),
+            SortType::ChildCount => compare_by_child_count(a, b, child_counts),
+            // The sort is stable, so equal entries keep their order.
+            SortType::None => return Ordering::Equal,
+        },
+    };
+
+    // Break ties by name so the result never depends on filesystem order
+    primary.then_with(|| compare_tiebreak(a.file_name(), b.file_name()))
+}
+
+/// Compares two files by the given file-only strategy.
+fn compare_files(
+    a: &DirEntry,
+    b: &DirEntry,
+    file_sort: FileSortType,
+    options: &SortOptions,
+    metadata: &MetadataCache,
+) -> Ordering {
+    match file_sort {
+        FileSortType::Name => compare_by_name(a, b, options),
+        FileSortType::Size => compare_by_size(b, a, metadata),
+        FileSortType::Modified => compare_by_modified(b, a, metadata),
+        FileSortType::Extension => compare_by_extension(a, b, options),
     }
 }
 
 /// Compares entries by name, handling case sensitivity and natural sorting.
 fn compare_by_name(a: &DirEntry, b: &DirEntry, options: &SortOptions) -> Ordering {
-    let name_a = a.file_name();
-    let name_b = b.file_name();
+    compare_names(a.file_name(), b.file_name(), options)
+}
 
+/// Compares file names, handling case sensitivity and natural sorting.
+fn compare_names(name_a: &OsStr, name_b: &OsStr, options: &SortOptions) -> Ordering {
     if options.natural_sort {
         compare_natural(name_a, name_b)
     } else if options.case_sensitive {
@@ -202,16 +340,16 @@ fn compare_by_name(a: &DirEntry, b: &DirEntry, options: &SortOptions) -> Orderin
 }
 
 /// Compares entries by file size, with directories having size 0.
-fn compare_by_size(a: &DirEntry, b: &DirEntry) -> Ordering {
-    let size_a = get_entry_size(a);
-    let size_b = get_entry_size(b);
+fn compare_by_size(a: &DirEntry, b: &DirEntry, metadata: &MetadataCache) -> Ordering {
+    let size_a = get_entry_size(a, metadata);
+    let size_b = get_entry_size(b, metadata);
     size_a.cmp(&size_b)
 }
 
 /// Compares entries by modification time.
-fn compare_by_modified(a: &DirEntry, b: &DirEntry) -> Ordering {
-    let modified_a = a.metadata().ok().and_then(|m| m.modified().ok());
-    let modified_b = b.metadata().ok().and_then(|m| m.modified().ok());
+fn compare_by_modified(a: &DirEntry, b: &DirEntry, metadata: &MetadataCache) -> Ordering {
+    let modified_a = with_metadata(a, metadata, |m| m.modified().ok());
+    let modified_b = with_metadata(b, metadata, |m| m.modified().ok());
 
     match (modified_a, modified_b) {
         (Some(a_time), Some(b_time)) => a_time.cmp(&b_time),
@@ -221,10 +359,32 @@ fn compare_by_modified(a: &DirEntry, b: &DirEntry) -> Ordering {
     }
 }
 
+/// Compares entries by creation time, newest first.
+fn compare_by_created(a: &DirEntry, b: &DirEntry, metadata: &MetadataCache) -> Ordering {
+    match (get_created_time(a, metadata), get_created_time(b, metadata)) {
+        (Some(a_time), Some(b_time)) => b_time.cmp(&a_time),
+        (Some(_), None) => Ordering::Less, // Files with known time sort first
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Compares entries by their number of immediate children, most first.
+fn compare_by_child_count(a: &DirEntry, b: &DirEntry, child_counts: &ChildCounts) -> Ordering {
+    let count = |entry: &DirEntry| child_counts.get(entry.path()).copied().unwrap_or(0);
+    count(b).cmp(&count(a))
+}
+
 /// Compares entries by file extension, falling back to name comparison.
 fn compare_by_extension(a: &DirEntry, b: &DirEntry, options: &SortOptions) -> Ordering {
-    let ext_a = get_extension(a.file_name());
-    let ext_b = get_extension(b.file_name());
+    compare_names_by_extension(a.file_name(), b.file_name(), options)
+}
+
+/// Compares file names by extension, then by the whole name. Names without an extension,
+/// including dotfiles such as `.bashrc`, have an empty one and so sort first.
+fn compare_names_by_extension(a: &OsStr, b: &OsStr, options: &SortOptions) -> Ordering {
+    let ext_a = get_extension(a);
+    let ext_b = get_extension(b);
 
     let ext_cmp = if options.case_sensitive {
         ext_a.cmp(&ext_b)
@@ -232,12 +392,8 @@ fn compare_by_extension(a: &DirEntry, b: &DirEntry, options: &SortOptions) -> Or
         compare_case_insensitive_str(&ext_a, &ext_b)
     };
 
-    // If extensions are equal, fall back to name comparison
-    if ext_cmp == Ordering::Equal {
-        compare_by_name(a, b, options)
-    } else {
-        ext_cmp
-    }
+    // Files sharing an extension are ordered by name
+    ext_cmp.then_with(|| compare_names(a, b, options))
 }
 
 /// Performs natural/version sorting comparison on OS strings.
@@ -257,6 +413,11 @@ fn compare_case_insensitive(a: &OsStr, b: &OsStr) -> Ordering {
     str_a.cmp(&str_b)
 }
 
+/// Orders names case-insensitively, then byte-wise, so distinct names never compare equal.
+fn compare_tiebreak(a: &OsStr, b: &OsStr) -> Ordering {
+    compare_case_insensitive(a, b).then_with(|| a.cmp(b))
+}
+
 /// Implements the default sort order: numbers first, then uppercase, then lowercase.
 fn compare_default_order(a: &OsStr, b: &OsStr) -> Ordering {
     let str_a = a.to_string_lossy();
@@ -310,17 +471,45 @@ fn compare_case_insensitive_str(a: &str, b: &str) -> Ordering {
 fn get_extension(filename: &OsStr) -> String {
     std::path::Path::new(filename)
         .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("")
-        .to_string()
+        .map(|ext| ext.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Reads a value from an entry's metadata, taking the metadata from `cache` when it is
+/// there, so a sort doesn't stat the same entry on every comparison.
+fn with_metadata<T>(
+    entry: &DirEntry,
+    cache: &MetadataCache,
+    read: impl FnOnce(&Metadata) -> Option<T>,
+) -> Option<T> {
+    match cache.get(entry.path()) {
+        Some(metadata) => read(metadata),
+        None => read(&entry.metadata().ok()?),
+    }
+}
+
+/// Gets the creation (birth) time of an entry, falling back to the modification time
+/// on filesystems that don't record it.
+fn get_created_time(entry: &DirEntry, metadata: &MetadataCache) -> Option<SystemTime> {
+    with_metadata(entry, metadata, |metadata| match metadata.created() {
+        Ok(time) => Some(time),
+        Err(_) => {
+            CREATED_FALLBACK_NOTICE.call_once(|| {
+                eprintln!(
+                    "lstr: creation time is not available here; sorting by modification time instead"
+                );
+            });
+            metadata.modified().ok()
+        }
+    })
 }
 
 /// Gets the size of a directory entry, returning 0 for directories.
-fn get_entry_size(entry: &DirEntry) -> u64 {
+fn get_entry_size(entry: &DirEntry, metadata: &MetadataCache) -> u64 {
     if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
         0 // Directories have size 0 for sorting purposes
     } else {
-        entry.metadata().ok().map(|m| m.len()).unwrap_or(0)
+        with_metadata(entry, metadata, |m| Some(m.len())).unwrap_or(0)
     }
 }
 
@@ -370,6 +559,13 @@ mod tests {
         assert_eq!(lexicographic_result, Ordering::Greater); // "file2.txt" > "file10.txt" lexicographically
     }
 
+    #[test]
+    fn test_tiebreak_is_total() {
+        assert_eq!(compare_tiebreak(OsStr::new("apple"), OsStr::new("Banana")), Ordering::Less);
+        assert_eq!(compare_tiebreak(OsStr::new("README"), OsStr::new("readme")), Ordering::Less);
+        assert_eq!(compare_tiebreak(OsStr::new("same"), OsStr::new("same")), Ordering::Equal);
+    }
+
     #[test]
     fn test_extension_extraction() {
         assert_eq!(get_extension(OsStr::new("file.txt")), "txt");
@@ -378,15 +574,44 @@ mod tests {
         assert_eq!(get_extension(OsStr::new(".hidden")), "");
     }
 
+    #[test]
+    fn test_extension_sort_order() {
+        let mut names = ["b.rs", "c.md", "A.rs", "Makefile", ".bashrc", "a.RS"].map(OsStr::new);
+        let options = SortOptions::default();
+        names.sort_by(|a, b| {
+            compare_names_by_extension(a, b, &options).then_with(|| compare_tiebreak(a, b))
+        });
+        // Extensionless names come first; a shared extension falls back to the name.
+        assert_eq!(names, [".bashrc", "Makefile", "c.md", "A.rs", "a.RS", "b.rs"].map(OsStr::new));
+    }
+
+    #[test]
+    fn test_reads_metadata() {
+        assert!(!SortOptions::default().reads_metadata());
+        let by_modified = SortOptions { sort_type: SortType::Modified, ..Default::default() };
+        assert!(by_modified.reads_metadata());
+        let files_by_size =
+            SortOptions { file_sort: Some(FileSortType::Size), ..Default::default() };
+        assert!(files_by_size.reads_metadata());
+        let by_extension = SortOptions { sort_type: SortType::Extension, ..Default::default() };
+        assert!(!by_extension.reads_metadata());
+    }
+
     #[test]
     fn test_sort_options_default() {
         let options = SortOptions::default();
         assert_eq!(options.sort_type, SortType::Name);
         assert!(!options.directories_first);
+        assert!(!options.directories_last);
         assert!(!options.case_sensitive);
         assert!(!options.natural_sort);
         assert!(!options.reverse);
+        assert!(!options.reverse_dirs);
+        assert!(!options.reverse_files);
+        assert!(!options.reverse_siblings);
         assert!(!options.dotfiles_first);
+        assert!(options.file_sort.is_none());
+        assert!(!options.dirs_by_name);
     }
 
     #[test]
@@ -0,0 +1,252 @@
+//! Parses and renders the `--format` line templates for the classic view.
+//!
+//! A template is literal text with `{field}` placeholders, each optionally followed by
+//! an alignment and width such as `{size:>8}`. Literal braces are written `{{` and `}}`.
+
+/// A value that can be placed in a line template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// The tree connectors (`│   ├── `)
+    Tree,
+    /// The entry name, styled like the default output
+    Name,
+    /// The entry's path as it was walked
+    Path,
+    /// The file icon (requires a Nerd Font)
+    Icon,
+    /// The permission string, e.g. `-rw-r--r--`
+    Perms,
+    /// The file size (blank for directories)
+    Size,
+    /// The file's content hash from --checksum (blank for directories)
+    Checksum,
+    /// How long ago the entry was modified, e.g. `2h`
+    Time,
+    /// The git status character
+    Git,
+    /// The walk error reported for the entry, if any
+    Error,
+}
+
+impl Field {
+    const ALL: [(&'static str, Field); 11] = [
+        ("tree", Field::Tree),
+        ("name", Field::Name),
+        ("path", Field::Path),
+        ("icon", Field::Icon),
+        ("perms", Field::Perms),
+        ("size", Field::Size),
+        ("checksum", Field::Checksum),
+        ("time", Field::Time),
+        ("mtime", Field::Time),
+        ("git", Field::Git),
+        ("error", Field::Error),
+    ];
+
+    /// Returns the field's placeholder name, as used in --output tsv headers.
+    pub fn name(self) -> &'static str {
+        Self::ALL.iter().find(|(_, field)| *field == self).map_or("", |(name, _)| name)
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|(n, _)| *n == name).map(|(_, field)| *field)
+    }
+
+    /// Parses a field by its placeholder name, as given to --fields.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        Self::from_name(name.trim()).ok_or_else(|| {
+            format!("unknown field '{name}' (expected one of: {})", Self::known_names())
+        })
+    }
+
+    /// Lists the field names for error messages.
+    fn known_names() -> String {
+        let names: Vec<&str> = Self::ALL.iter().map(|(n, _)| *n).collect();
+        names.join(", ")
+    }
+}
+
+/// How a field is padded when it is narrower than its width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field { field: Field, align: Align, width: usize },
+}
+
+/// A parsed `--format` template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineTemplate {
+    segments: Vec<Segment>,
+}
+
+impl LineTemplate {
+    /// Parses a template, rejecting unknown placeholders and malformed specifiers.
+    ///
+    /// If the template has no `{tree}` placeholder, the tree connectors are drawn
+    /// right before `{name}` (or at the start of the line if there is no name either).
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut spec = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => spec.push(c),
+                            None => return Err(format!("unclosed placeholder '{{{spec}'")),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(parse_placeholder(&spec)?);
+                }
+                '}' => return Err("unmatched '}' (write '}}' for a literal brace)".to_string()),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        let has_field = |wanted: Field| {
+            segments.iter().any(|s| matches!(s, Segment::Field { field, .. } if *field == wanted))
+        };
+        if !has_field(Field::Tree) {
+            let position = segments
+                .iter()
+                .position(|s| matches!(s, Segment::Field { field: Field::Name, .. }))
+                .unwrap_or(0);
+            let tree = Segment::Field { field: Field::Tree, align: Align::Left, width: 0 };
+            segments.insert(position, tree);
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Returns whether the template contains a placeholder for `field`.
+    pub fn uses(&self, field: Field) -> bool {
+        self.segments.iter().any(|s| matches!(s, Segment::Field { field: f, .. } if *f == field))
+    }
+
+    /// Builds a line, asking `value` for each field's rendered text and its display width.
+    pub fn render(&self, mut value: impl FnMut(Field) -> (String, usize)) -> String {
+        let mut line = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => line.push_str(text),
+                Segment::Field { field, align, width } => {
+                    let (text, text_width) = value(*field);
+                    let padding = width.saturating_sub(text_width);
+                    let (before, after) = match align {
+                        Align::Left => (0, padding),
+                        Align::Right => (padding, 0),
+                        Align::Center => (padding / 2, padding - padding / 2),
+                    };
+                    line.push_str(&" ".repeat(before));
+                    line.push_str(&text);
+                    line.push_str(&" ".repeat(after));
+                }
+            }
+        }
+        line
+    }
+}
+
+/// Parses the inside of a placeholder, e.g. `size:>8`.
+fn parse_placeholder(spec: &str) -> Result<Segment, String> {
+    let (name, format) = spec.split_once(':').unwrap_or((spec, ""));
+    let Some(field) = Field::from_name(name.trim()) else {
+        return Err(format!(
+            "unknown placeholder '{{{name}}}' (expected one of: {})",
+            Field::known_names()
+        ));
+    };
+
+    let (align, width) = match format.chars().next() {
+        Some('<') => (Align::Left, &format[1..]),
+        Some('>') => (Align::Right, &format[1..]),
+        Some('^') => (Align::Center, &format[1..]),
+        _ => (Align::Left, format),
+    };
+    let width = if width.is_empty() {
+        0
+    } else {
+        width.parse().map_err(|_| format!("invalid width '{width}' in '{{{spec}}}'"))?
+    };
+
+    Ok(Segment::Field { field, align, width })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_plain(template: &LineTemplate) -> String {
+        template.render(|field| {
+            let text = match field {
+                Field::Tree => "├── ",
+                Field::Name => "main.rs",
+                Field::Size => "1.0 KiB",
+                Field::Perms => "-rw-r--r--",
+                _ => "",
+            };
+            (text.to_string(), text.chars().count())
+        })
+    }
+
+    #[test]
+    fn test_render_with_alignment() {
+        let template = LineTemplate::parse("{perms} {size:>9} {name}").unwrap();
+        assert_eq!(render_plain(&template), "-rw-r--r--   1.0 KiB ├── main.rs");
+    }
+
+    #[test]
+    fn test_explicit_tree_and_escapes() {
+        let template = LineTemplate::parse("{tree}{name:^11}{{x}}").unwrap();
+        assert_eq!(render_plain(&template), "├──   main.rs  {x}");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(LineTemplate::parse("{bogus}").unwrap_err().contains("unknown placeholder"));
+        assert!(LineTemplate::parse("{name").is_err());
+        assert!(LineTemplate::parse("name}").is_err());
+        assert!(LineTemplate::parse("{size:>x}").is_err());
+    }
+
+    #[test]
+    fn test_parse_field() {
+        assert_eq!(Field::parse("size"), Ok(Field::Size));
+        assert_eq!(Field::parse(" path "), Ok(Field::Path));
+        assert_eq!(Field::parse("mtime"), Field::parse("time"));
+        assert!(Field::parse("bogus").unwrap_err().contains("unknown field"));
+    }
+
+    #[test]
+    fn test_uses() {
+        let template = LineTemplate::parse("{git} {name}").unwrap();
+        assert!(template.uses(Field::Git));
+        assert!(template.uses(Field::Tree));
+        assert!(!template.uses(Field::Size));
+    }
+}
@@ -3,38 +3,55 @@
 //! This module contains all logic for running `lstr` in an interactive
 //! session, including state management, event handling, and rendering.
 
-use crate::app::InteractiveArgs;
-use crate::git::{self, StatusCache};
+use crate::app::{GitSubmodules, InteractiveArgs, SortType, Theme};
+use crate::git::{self, GitRepoStatus};
 use crate::icons;
 use crate::sort;
 use crate::utils;
+use crate::watch;
+use clap::ValueEnum;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
 use ignore::WalkBuilder;
 use lscolors::{Color as LsColor, LsColors, Style as LsStyle};
 use ratatui::crossterm::{
     event::{
         self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+        MouseButton, MouseEvent, MouseEventKind,
     },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame, Terminal,
 };
+use std::collections::HashSet;
 use std::env;
 use std::fs;
 use std::io::{stderr, stdout, IsTerminal, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::{Duration, SystemTime};
+use unicode_width::UnicodeWidthChar;
 
 // Platform-specific import for unix permissions
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
+/// The marker drawn in front of the selected row.
+const HIGHLIGHT_SYMBOL: &str = "> ";
+
+/// The number of columns moved per horizontal scroll step.
+const HORIZONTAL_SCROLL_STEP: usize = 4;
+
+/// How long to wait for terminal input before checking for filesystem changes in watch mode.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// Converts an lscolors::Style to a ratatui::style::Style
 fn to_ratatui_style(ls_style: LsStyle) -> Style {
     let mut style = Style::default();
@@ -75,6 +92,92 @@ fn to_ratatui_style(ls_style: LsStyle) -> Style {
     style
 }
 
+/// The colors the interface draws with, picked by --theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Palette {
+    /// Directory names that LS_COLORS leaves uncolored
+    directory: Color,
+    /// Secondary text: permissions, the time and size columns, and hints
+    muted: Color,
+    /// The status line
+    status: Color,
+    /// The status line while searching, and the fuzzy finder prompt
+    accent: Color,
+    git_new: Color,
+    git_modified: Color,
+    git_deleted: Color,
+    git_conflicted: Color,
+    git_untracked: Color,
+    git_submodule: Color,
+}
+
+impl Palette {
+    fn for_theme(theme: Theme) -> Self {
+        match theme {
+            Theme::Default => Self {
+                directory: Color::Blue,
+                muted: Color::DarkGray,
+                status: Color::Gray,
+                accent: Color::Yellow,
+                git_new: Color::Green,
+                git_modified: Color::Yellow,
+                git_deleted: Color::Red,
+                git_conflicted: Color::LightRed,
+                git_untracked: Color::Magenta,
+                git_submodule: Color::Cyan,
+            },
+            // 256-color shades dark enough to read on white.
+            Theme::Light => Self {
+                directory: Color::Indexed(25),
+                muted: Color::Indexed(240),
+                status: Color::Indexed(236),
+                accent: Color::Indexed(130),
+                git_new: Color::Indexed(28),
+                git_modified: Color::Indexed(130),
+                git_deleted: Color::Indexed(124),
+                git_conflicted: Color::Indexed(160),
+                git_untracked: Color::Indexed(90),
+                git_submodule: Color::Indexed(31),
+            },
+            Theme::HighContrast => Self {
+                directory: Color::LightBlue,
+                muted: Color::White,
+                status: Color::White,
+                accent: Color::LightYellow,
+                git_new: Color::LightGreen,
+                git_modified: Color::LightYellow,
+                git_deleted: Color::LightRed,
+                git_conflicted: Color::LightRed,
+                git_untracked: Color::LightMagenta,
+                git_submodule: Color::LightCyan,
+            },
+            Theme::Solarized => Self {
+                directory: Color::Rgb(38, 139, 210),
+                muted: Color::Rgb(88, 110, 117),
+                status: Color::Rgb(147, 161, 161),
+                accent: Color::Rgb(181, 137, 0),
+                git_new: Color::Rgb(133, 153, 0),
+                git_modified: Color::Rgb(181, 137, 0),
+                git_deleted: Color::Rgb(220, 50, 47),
+                git_conflicted: Color::Rgb(203, 75, 22),
+                git_untracked: Color::Rgb(211, 54, 130),
+                git_submodule: Color::Rgb(42, 161, 152),
+            },
+        }
+    }
+
+    fn git_status(&self, status: git::FileStatus) -> Color {
+        match status {
+            git::FileStatus::New | git::FileStatus::Renamed => self.git_new,
+            git::FileStatus::Modified | git::FileStatus::Typechange => self.git_modified,
+            git::FileStatus::Deleted => self.git_deleted,
+            git::FileStatus::Conflicted => self.git_conflicted,
+            git::FileStatus::Untracked => self.git_untracked,
+            git::FileStatus::Submodule => self.git_submodule,
+        }
+    }
+}
+
 enum PostExitAction {
     None,
     OpenFile(PathBuf),
@@ -90,6 +193,15 @@ struct FileEntry {
     size: Option<u64>,
     permissions: Option<String>,
     git_status: Option<git::FileStatus>,
+    /// Whether a path somewhere inside the directory has a git status
+    contains_changes: bool,
+    modified: Option<SystemTime>,
+    kind: utils::FileKind,
+    /// Whether any execute bit is set; only read with --classify
+    executable: bool,
+    /// Whether the directory's children are in the tree; with --lazy, false until it is
+    /// first expanded
+    children_loaded: bool,
 }
 
 /// Represents the current search mode of the TUI
@@ -101,6 +213,166 @@ enum SearchMode {
     Search,
 }
 
+/// The settings offered by the sort/filter menu, in display order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MenuItem {
+    Sort,
+    DirsFirst,
+    Hidden,
+    Gitignore,
+}
+
+const MENU_ITEMS: [MenuItem; 4] =
+    [MenuItem::Sort, MenuItem::DirsFirst, MenuItem::Hidden, MenuItem::Gitignore];
+
+/// Every key binding and its action, as listed in the help overlay.
+const KEY_BINDINGS: &[(&str, &str)] = &[
+    ("↑ / k", "Move selection up"),
+    ("↓ / j", "Move selection down"),
+    ("← / h", "Scroll left (--navigate: go back to the parent)"),
+    ("→ / l", "Scroll right (--navigate: enter the directory)"),
+    ("Enter", "Open file in $EDITOR / toggle directory (--focus: focus it)"),
+    ("Backspace", "Return from a focused directory (--focus)"),
+    ("Click", "Select an entry; click a selected directory to toggle it"),
+    ("Mouse wheel", "Move selection up / down"),
+    ("/", "Search by name (Esc to clear)"),
+    ("Ctrl+f", "Fuzzy-find any path and jump to it"),
+    ("F", "Open the sort/filter menu"),
+    (":", "Run a command: expand-all, collapse-all, sort, toggle, goto (Tab completes)"),
+    ("d", "Toggle showing directories only"),
+    ("r", "Toggle a flat list of files, most recently modified first"),
+    ("?", "Show this help"),
+    ("Ctrl+s", "Quit and print the selected path"),
+    ("q / Esc", "Quit"),
+];
+
+/// The commands of the `:` command line.
+const COMMANDS: &[&str] = &["expand-all", "collapse-all", "sort", "toggle", "goto"];
+
+/// The settings the `toggle` command flips.
+const TOGGLE_SETTINGS: &[&str] = &["hidden", "gitignore", "dirs-first", "dirs-last", "dirs-only"];
+
+/// Pending sort/filter settings edited in the menu overlay, applied when it closes
+#[derive(Debug, Clone)]
+struct OptionsMenu {
+    selected: usize,
+    sort: SortType,
+    dirs_first: bool,
+    all: bool,
+    gitignore: bool,
+}
+
+impl OptionsMenu {
+    fn new(args: &InteractiveArgs) -> Self {
+        Self {
+            selected: 0,
+            sort: args.sort,
+            dirs_first: args.dirs_first,
+            all: args.all,
+            gitignore: args.gitignore,
+        }
+    }
+
+    fn next(&mut self) {
+        self.selected = (self.selected + 1) % MENU_ITEMS.len();
+    }
+
+    fn previous(&mut self) {
+        self.selected = (self.selected + MENU_ITEMS.len() - 1) % MENU_ITEMS.len();
+    }
+
+    /// Cycle the sort mode or flip the toggle under the cursor
+    fn change_selected(&mut self) {
+        match MENU_ITEMS[self.selected] {
+            MenuItem::Sort => {
+                let variants = SortType::value_variants();
+                let current = variants.iter().position(|v| *v == self.sort).unwrap_or(0);
+                self.sort = variants[(current + 1) % variants.len()];
+            }
+            MenuItem::DirsFirst => self.dirs_first = !self.dirs_first,
+            MenuItem::Hidden => self.all = !self.all,
+            MenuItem::Gitignore => self.gitignore = !self.gitignore,
+        }
+    }
+
+    /// Copy the pending settings into the interactive arguments
+    fn apply(&self, args: &mut InteractiveArgs) {
+        args.sort = self.sort;
+        args.dirs_first = self.dirs_first;
+        args.all = self.all;
+        args.gitignore = self.gitignore;
+    }
+
+    fn label(&self, item: MenuItem) -> String {
+        let check = |on: bool| if on { "[x]" } else { "[ ]" };
+        match item {
+            MenuItem::Sort => format!("Sort by: {}", self.sort),
+            MenuItem::DirsFirst => format!("{} Directories first", check(self.dirs_first)),
+            MenuItem::Hidden => format!("{} Show hidden files", check(self.all)),
+            MenuItem::Gitignore => format!("{} Respect .gitignore", check(self.gitignore)),
+        }
+    }
+}
+
+/// The fuzzy finder overlay: a query over every entry path, with ranked matches
+struct FuzzyFinder {
+    matcher: SkimMatcherV2,
+    query: String,
+    /// Indices into `master_entries`, best match first
+    results: Vec<usize>,
+    /// Position of the highlighted match within `results`
+    selected: usize,
+}
+
+impl FuzzyFinder {
+    fn new(entries: &[FileEntry]) -> Self {
+        let mut finder = Self {
+            matcher: SkimMatcherV2::default(),
+            query: String::new(),
+            results: Vec::new(),
+            selected: 0,
+        };
+        finder.update(entries);
+        finder
+    }
+
+    /// Re-ranks the entries against the query and highlights the best match
+    fn update(&mut self, entries: &[FileEntry]) {
+        let mut scored: Vec<(i64, usize)> = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                let score = self.matcher.fuzzy_match(&relative_path(entry), &self.query)?;
+                Some((score, i))
+            })
+            .collect();
+        scored.sort_by(|(score_a, i_a), (score_b, i_b)| score_b.cmp(score_a).then(i_a.cmp(i_b)));
+        self.results = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    fn push(&mut self, c: char, entries: &[FileEntry]) {
+        self.query.push(c);
+        self.update(entries);
+    }
+
+    fn pop(&mut self, entries: &[FileEntry]) {
+        if self.query.pop().is_some() {
+            self.update(entries);
+        }
+    }
+
+    fn next(&mut self) {
+        if self.selected + 1 < self.results.len() {
+            self.selected += 1;
+        }
+    }
+
+    fn previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
+
 struct AppState {
     master_entries: Vec<FileEntry>,
     visible_entries: Vec<FileEntry>,
@@ -111,14 +383,39 @@ struct AppState {
     search_query: String,
     /// Backup of visible entries before search/filter was applied
     original_visible_entries: Vec<FileEntry>,
+    /// Number of columns the list is scrolled to the right
+    horizontal_offset: usize,
+    /// Number of list rows visible on screen, updated on every draw
+    viewport_height: usize,
+    /// Where the list was last drawn, to tell which entry a mouse click lands on
+    list_area: Rect,
+    /// The sort/filter menu overlay, when open
+    options_menu: Option<OptionsMenu>,
+    /// Whether the key binding help overlay is open
+    show_help: bool,
+    /// The fuzzy finder overlay, when open
+    fuzzy_finder: Option<FuzzyFinder>,
+    /// Directories entered in --navigate mode, innermost last; the tree is rooted at the last one
+    root_stack: Vec<PathBuf>,
+    /// The directory this tree is rooted at when it was opened with --focus
+    focus_root: Option<PathBuf>,
+    /// The tree this one was focused from, kept as it was; together these form a stack
+    outer: Option<Box<AppState>>,
+    /// The git status of the tree, kept to mark the entries scanned later with --lazy
+    git_repo_status: Option<GitRepoStatus>,
+    /// Whether a flat list of files, most recently modified first, replaces the tree
+    recent_view: bool,
+    /// The `:` command line being typed, when open
+    command_line: Option<String>,
+    /// Feedback from the last command, shown in the status line until the next key
+    command_message: Option<String>,
 }
 
 impl AppState {
     fn new(args: &InteractiveArgs, root_path: &Path) -> anyhow::Result<Self> {
-        let git_repo_status = if args.git_status { git::load_status(root_path)? } else { None };
-
-        let status_info = git_repo_status.as_ref().map(|s| (&s.cache, &s.root));
-        let mut master_entries = scan_directory(root_path, status_info, args)?;
+        let git_repo_status = load_git_status(args, root_path)?;
+        let mut master_entries =
+            scan_directory(root_path, scan_depth(args), git_repo_status.as_ref(), args)?;
 
         if let Some(expand_level) = args.expand_level {
             for entry in &mut master_entries {
@@ -127,6 +424,20 @@ impl AppState {
                 }
             }
         }
+        if !args.expand_match.is_empty() {
+            let patterns = utils::glob_set(&args.expand_match)?;
+            let matched: Vec<PathBuf> = master_entries
+                .iter()
+                .filter(|e| e.is_dir && patterns.is_match(relative_path(e)))
+                .map(|e| e.path.clone())
+                .collect();
+            // Their ancestors are expanded too, so the matches are visible.
+            for entry in master_entries.iter_mut().filter(|e| e.is_dir) {
+                if matched.iter().any(|path| path.starts_with(&entry.path)) {
+                    entry.is_expanded = true;
+                }
+            }
+        }
 
         let mut app_state = Self {
             master_entries,
@@ -135,6 +446,19 @@ impl AppState {
             search_mode: SearchMode::None,
             search_query: String::new(),
             original_visible_entries: Vec::new(),
+            horizontal_offset: 0,
+            viewport_height: 0,
+            list_area: Rect::default(),
+            options_menu: None,
+            show_help: false,
+            root_stack: Vec::new(),
+            focus_root: None,
+            outer: None,
+            fuzzy_finder: None,
+            git_repo_status,
+            recent_view: false,
+            command_line: None,
+            command_message: None,
         };
         app_state.regenerate_visible_entries();
         if !app_state.visible_entries.is_empty() {
@@ -143,8 +467,183 @@ impl AppState {
         Ok(app_state)
     }
 
+    /// Re-scans the tree from disk, preserving expansion state and the selected entry.
+    ///
+    /// With --lazy, the directories loaded so far are scanned again, and no others.
+    fn refresh(&mut self, args: &InteractiveArgs, root_path: &Path) -> anyhow::Result<()> {
+        let expanded: HashSet<PathBuf> =
+            self.master_entries.iter().filter(|e| e.is_expanded).map(|e| e.path.clone()).collect();
+        let loaded: HashSet<PathBuf> = self
+            .master_entries
+            .iter()
+            .filter(|e| e.is_dir && e.children_loaded)
+            .map(|e| e.path.clone())
+            .collect();
+        let selected_path = self.get_selected_entry().map(|e| e.path.clone());
+
+        let root = self.current_root(root_path).to_path_buf();
+        self.git_repo_status = load_git_status(args, &root)?;
+        self.master_entries =
+            scan_directory(&root, scan_depth(args), self.git_repo_status.as_ref(), args)?;
+        // Children are inserted right after their directory, so nested ones are reached too.
+        let mut index = 0;
+        while index < self.master_entries.len() {
+            let entry = &self.master_entries[index];
+            if entry.is_dir && !entry.children_loaded && loaded.contains(&entry.path) {
+                self.load_children(index, args)?;
+            }
+            index += 1;
+        }
+        for entry in &mut self.master_entries {
+            entry.is_expanded = expanded.contains(&entry.path);
+        }
+
+        self.regenerate_visible_entries();
+        if self.in_search_mode() {
+            self.original_visible_entries = self.visible_entries.clone();
+            self.apply_search_filter();
+        }
+
+        let new_selection = if self.visible_entries.is_empty() {
+            None
+        } else {
+            selected_path
+                .and_then(|path| self.visible_entries.iter().position(|e| e.path == path))
+                .or_else(|| {
+                    self.list_state.selected().map(|i| i.min(self.visible_entries.len() - 1))
+                })
+        };
+        self.list_state.select(new_selection);
+        Ok(())
+    }
+
+    /// Returns the directory the tree is currently rooted at.
+    fn current_root<'a>(&'a self, root_path: &'a Path) -> &'a Path {
+        self.root_stack.last().or(self.focus_root.as_ref()).map_or(root_path, PathBuf::as_path)
+    }
+
+    /// Replaces the tree with one rooted at the selected directory (--focus mode). The
+    /// current tree, expansion state included, is set aside until [`Self::unfocus`].
+    fn focus_selected_directory(&mut self, args: &InteractiveArgs) -> anyhow::Result<()> {
+        let Some(dir) = self.get_selected_entry().filter(|e| e.is_dir).map(|e| e.path.clone())
+        else {
+            return Ok(());
+        };
+        let mut inner = AppState::new(args, &dir)?;
+        inner.focus_root = Some(dir);
+        inner.viewport_height = self.viewport_height;
+        let outer = std::mem::replace(self, inner);
+        self.outer = Some(Box::new(outer));
+        Ok(())
+    }
+
+    /// Returns to the tree that was focused from, re-scanning it to pick up changes made
+    /// in the meantime (--focus mode).
+    fn unfocus(&mut self, args: &InteractiveArgs, root_path: &Path) -> anyhow::Result<()> {
+        let Some(outer) = self.outer.take() else {
+            return Ok(());
+        };
+        *self = *outer;
+        self.refresh(args, root_path)
+    }
+
+    /// Makes the selected directory the root of the tree (--navigate mode).
+    fn enter_selected_directory(
+        &mut self,
+        args: &InteractiveArgs,
+        root_path: &Path,
+    ) -> anyhow::Result<()> {
+        let Some(dir) = self.get_selected_entry().filter(|e| e.is_dir).map(|e| e.path.clone())
+        else {
+            return Ok(());
+        };
+        self.root_stack.push(dir);
+        self.horizontal_offset = 0;
+        self.list_state.select(Some(0));
+        self.refresh(args, root_path)
+    }
+
+    /// Flips --dirs-only and re-scans. A selected file hands the selection to its directory.
+    fn toggle_dirs_only(
+        &mut self,
+        args: &mut InteractiveArgs,
+        root_path: &Path,
+    ) -> anyhow::Result<()> {
+        args.dirs_only = !args.dirs_only;
+        let file_parent = self
+            .get_selected_entry()
+            .filter(|e| !e.is_dir)
+            .and_then(|e| e.path.parent())
+            .map(Path::to_path_buf);
+        if let Some(index) =
+            file_parent.and_then(|p| self.visible_entries.iter().position(|e| e.path == p))
+        {
+            self.list_state.select(Some(index));
+        }
+        self.refresh(args, root_path)
+    }
+
+    /// Returns to the previous root and selects the directory that was left (--navigate mode).
+    /// Does nothing at the root lstr was started in, which the tree never goes above.
+    fn go_to_parent(&mut self, args: &InteractiveArgs, root_path: &Path) -> anyhow::Result<()> {
+        let Some(child) = self.root_stack.pop() else {
+            return Ok(());
+        };
+        self.horizontal_offset = 0;
+        self.refresh(args, root_path)?;
+        if let Some(index) = self.visible_entries.iter().position(|e| e.path == child) {
+            self.list_state.select(Some(index));
+        }
+        Ok(())
+    }
+
+    /// Switches between the tree and the flat list of recently modified files. The
+    /// selected file stays selected, with its directories expanded in the tree.
+    fn toggle_recent_view(&mut self) {
+        let selected_path = self.get_selected_entry().map(|e| e.path.clone());
+        self.recent_view = !self.recent_view;
+        if let Some(path) = selected_path.as_deref().filter(|_| !self.recent_view) {
+            self.expand_ancestors(path);
+        }
+        self.horizontal_offset = 0;
+        self.regenerate_visible_entries();
+        let new_selection = if self.visible_entries.is_empty() {
+            None
+        } else {
+            let index = selected_path
+                .and_then(|path| self.visible_entries.iter().position(|e| e.path == path));
+            Some(index.unwrap_or(0))
+        };
+        self.list_state.select(new_selection);
+    }
+
+    /// Expands every directory above `path`, so the tree shows it.
+    fn expand_ancestors(&mut self, path: &Path) {
+        for entry in &mut self.master_entries {
+            if entry.is_dir && entry.path != path && path.starts_with(&entry.path) {
+                entry.is_expanded = true;
+            }
+        }
+    }
+
+    /// Reads the modification time of the files that don't have one yet, which is all of
+    /// them unless --time is on.
+    fn load_modified_times(&mut self) {
+        for entry in self.master_entries.iter_mut().filter(|e| !e.is_dir && e.modified.is_none()) {
+            entry.modified = fs::symlink_metadata(&entry.path).and_then(|md| md.modified()).ok();
+        }
+    }
+
     fn regenerate_visible_entries(&mut self) {
         self.visible_entries.clear();
+        if self.recent_view {
+            self.load_modified_times();
+            self.visible_entries =
+                self.master_entries.iter().filter(|e| !e.is_dir).cloned().collect();
+            // Newest first; files whose time couldn't be read go last, in tree order.
+            self.visible_entries.sort_by_key(|e| std::cmp::Reverse(e.modified));
+            return;
+        }
         let mut parent_expanded_stack: Vec<bool> = Vec::new();
         for entry in &self.master_entries {
             while parent_expanded_stack.len() >= entry.depth {
@@ -187,10 +686,112 @@ impl AppState {
         self.list_state.select(Some(i));
     }
 
+    /// Scroll the list left by one step
+    fn scroll_left(&mut self) {
+        self.horizontal_offset = self.horizontal_offset.saturating_sub(HORIZONTAL_SCROLL_STEP);
+    }
+
+    /// Scroll the list right by one step (clamped to the longest line when drawn)
+    fn scroll_right(&mut self) {
+        self.horizontal_offset += HORIZONTAL_SCROLL_STEP;
+    }
+
+    /// Scroll the list so the selected row sits in the middle of the viewport when possible
+    fn center_selection(&mut self) {
+        let Some(selected) = self.list_state.selected() else {
+            return;
+        };
+        let max_offset = self.visible_entries.len().saturating_sub(self.viewport_height);
+        *self.list_state.offset_mut() =
+            selected.saturating_sub(self.viewport_height / 2).min(max_offset);
+    }
+
+    /// Adapts the list to a new terminal height ahead of the next draw
+    fn handle_resize(&mut self, terminal_height: u16) {
+        // The status line takes up the last row.
+        self.viewport_height = usize::from(terminal_height).saturating_sub(1);
+        // Growing the terminal should reveal entries above rather than blank rows below.
+        let max_offset = self.visible_entries.len().saturating_sub(self.viewport_height);
+        let offset = self.list_state.offset().min(max_offset);
+        *self.list_state.offset_mut() = offset;
+    }
+
+    /// Returns whether an overlay or the command line has the keyboard, and the list
+    /// doesn't respond to the mouse.
+    fn overlay_open(&self) -> bool {
+        self.show_help
+            || self.fuzzy_finder.is_some()
+            || self.options_menu.is_some()
+            || self.command_line.is_some()
+    }
+
+    /// Handles a mouse event over the list: the wheel moves the selection, and a click
+    /// selects an entry, or toggles a directory that is already selected.
+    fn handle_mouse(&mut self, mouse: MouseEvent, args: &InteractiveArgs) -> anyhow::Result<()> {
+        let area = self.list_area;
+        let inside = (area.x..area.x + area.width).contains(&mouse.column)
+            && (area.y..area.y + area.height).contains(&mouse.row);
+        let (Some(selected), true) = (self.list_state.selected(), inside) else {
+            return Ok(());
+        };
+        match mouse.kind {
+            // Unlike j/k, the wheel stops at either end of the list.
+            MouseEventKind::ScrollDown => {
+                let last = self.visible_entries.len().saturating_sub(1);
+                self.list_state.select(Some((selected + 1).min(last)));
+            }
+            MouseEventKind::ScrollUp => self.list_state.select(Some(selected.saturating_sub(1))),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let index = self.list_state.offset() + usize::from(mouse.row - area.y);
+                let Some(entry) = self.visible_entries.get(index) else {
+                    return Ok(());
+                };
+                if index != selected || !entry.is_dir {
+                    self.list_state.select(Some(index));
+                } else if args.focus {
+                    self.focus_selected_directory(args)?;
+                } else {
+                    self.load_selected_children(args)?;
+                    self.toggle_selected_directory();
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     fn get_selected_entry(&self) -> Option<&FileEntry> {
         self.list_state.selected().and_then(|i| self.visible_entries.get(i))
     }
 
+    /// Scans the selected directory's children if they haven't been yet (--lazy mode).
+    fn load_selected_children(&mut self, args: &InteractiveArgs) -> anyhow::Result<()> {
+        let Some(selected_path) = self.get_selected_entry().map(|e| e.path.clone()) else {
+            return Ok(());
+        };
+        match self.master_entries.iter().position(|e| e.path == selected_path) {
+            Some(index) if !self.master_entries[index].children_loaded => {
+                self.load_children(index, args)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Scans the immediate children of the directory at `index` in `master_entries` and
+    /// inserts them right after it.
+    fn load_children(&mut self, index: usize, args: &InteractiveArgs) -> anyhow::Result<()> {
+        let parent = &self.master_entries[index];
+        let parent_depth = parent.depth;
+        let mut children =
+            scan_directory(&parent.path, Some(1), self.git_repo_status.as_ref(), args)?;
+        for child in &mut children {
+            child.depth += parent_depth;
+        }
+        self.master_entries[index].children_loaded = true;
+        self.master_entries.splice(index + 1..index + 1, children);
+        Ok(())
+    }
+
     fn toggle_selected_directory(&mut self) {
         if let Some(selected_index) = self.list_state.selected() {
             let selected_path = self.visible_entries[selected_index].path.clone();
@@ -213,6 +814,157 @@ impl AppState {
         }
     }
 
+    fn open_fuzzy_finder(&mut self) {
+        self.fuzzy_finder = Some(FuzzyFinder::new(&self.master_entries));
+    }
+
+    /// Closes the fuzzy finder and selects its highlighted match, expanding its ancestors
+    fn jump_to_fuzzy_match(&mut self) {
+        let Some(finder) = self.fuzzy_finder.take() else {
+            return;
+        };
+        let Some(target) = finder.results.get(finder.selected).map(|&i| &self.master_entries[i])
+        else {
+            return;
+        };
+        let target = target.path.clone();
+        // The match may be a directory, which only the tree shows.
+        self.recent_view = false;
+        self.expand_ancestors(&target);
+        self.regenerate_visible_entries();
+        if let Some(index) = self.visible_entries.iter().position(|e| e.path == target) {
+            self.list_state.select(Some(index));
+        }
+    }
+
+    /// Runs the command typed on the `:` command line and closes it. Mistakes, such as an
+    /// unknown command, are reported in the status line.
+    fn run_command_line(
+        &mut self,
+        args: &mut InteractiveArgs,
+        root_path: &Path,
+    ) -> anyhow::Result<()> {
+        let Some(line) = self.command_line.take() else {
+            return Ok(());
+        };
+        let line = line.trim();
+        let (command, argument) =
+            line.split_once(' ').map_or((line, ""), |(command, arg)| (command, arg.trim()));
+        match (command, argument) {
+            ("", _) => {}
+            ("expand-all", "") => self.set_all_expanded(true, args)?,
+            ("collapse-all", "") => self.set_all_expanded(false, args)?,
+            ("sort", name) => match SortType::from_str(name, true) {
+                Ok(sort) => {
+                    args.sort = sort;
+                    self.refresh(args, root_path)?;
+                }
+                Err(_) => self.command_message = Some(format!("Unknown sort type: {name}")),
+            },
+            ("toggle", "dirs-only") => self.toggle_dirs_only(args, root_path)?,
+            ("toggle", setting) => {
+                let flag = match setting {
+                    "hidden" => &mut args.all,
+                    "gitignore" => &mut args.gitignore,
+                    "dirs-first" => &mut args.dirs_first,
+                    "dirs-last" => &mut args.dirs_last,
+                    _ => {
+                        self.command_message = Some(format!("Unknown setting: {setting}"));
+                        return Ok(());
+                    }
+                };
+                *flag = !*flag;
+                self.refresh(args, root_path)?;
+            }
+            ("goto", path) if !path.is_empty() => {
+                self.go_to_path(Path::new(path), args, root_path)?;
+            }
+            _ => self.command_message = Some(format!("Unknown command: {line}")),
+        }
+        Ok(())
+    }
+
+    /// Completes the word being typed on the command line, listing the candidates in the
+    /// status line when there are several.
+    fn complete_command_line(&mut self) {
+        let Some(line) = self.command_line.as_mut() else {
+            return;
+        };
+        let (completed, candidates) = complete_command(line);
+        *line = completed;
+        if candidates.len() > 1 {
+            self.command_message = Some(candidates.join("  "));
+        }
+    }
+
+    /// Expands or collapses every directory. With --lazy, expanding scans the directories
+    /// that haven't been yet, so the whole tree is read.
+    fn set_all_expanded(&mut self, expanded: bool, args: &InteractiveArgs) -> anyhow::Result<()> {
+        let selected_path = self.get_selected_entry().map(|e| e.path.clone());
+        if expanded {
+            // Children are inserted right after their directory, so nested ones are reached too.
+            let mut index = 0;
+            while index < self.master_entries.len() {
+                let entry = &self.master_entries[index];
+                if entry.is_dir && !entry.children_loaded {
+                    self.load_children(index, args)?;
+                }
+                index += 1;
+            }
+        }
+        for entry in self.master_entries.iter_mut().filter(|e| e.is_dir) {
+            entry.is_expanded = expanded;
+        }
+        self.recent_view = false;
+        self.regenerate_visible_entries();
+        // A selection hidden by collapsing moves to the directory it was in.
+        let new_selection = selected_path
+            .and_then(|path| {
+                path.ancestors()
+                    .find_map(|dir| self.visible_entries.iter().position(|e| e.path == dir))
+            })
+            .or((!self.visible_entries.is_empty()).then_some(0));
+        self.list_state.select(new_selection);
+        Ok(())
+    }
+
+    /// Selects the entry at `path`, relative to the root unless absolute, expanding the
+    /// directories above it. With --lazy, those directories are scanned first.
+    fn go_to_path(
+        &mut self,
+        path: &Path,
+        args: &InteractiveArgs,
+        root_path: &Path,
+    ) -> anyhow::Result<()> {
+        let root = self.current_root(root_path).to_path_buf();
+        let target = root.join(path);
+        let mut dirs: Vec<&Path> =
+            target.ancestors().skip(1).take_while(|dir| dir.starts_with(&root)).collect();
+        dirs.reverse();
+        for dir in dirs {
+            if let Some(index) = self
+                .master_entries
+                .iter()
+                .position(|e| e.path == dir && e.is_dir && !e.children_loaded)
+            {
+                self.load_children(index, args)?;
+            }
+        }
+
+        if !self.master_entries.iter().any(|e| e.path == target) {
+            self.command_message = Some(format!("Not in the tree: {}", path.display()));
+            return Ok(());
+        }
+        // The target may be a directory, which only the tree shows.
+        self.recent_view = false;
+        self.expand_ancestors(&target);
+        self.regenerate_visible_entries();
+        if let Some(index) = self.visible_entries.iter().position(|e| e.path == target) {
+            self.list_state.select(Some(index));
+        }
+        Ok(())
+    }
+
     /// Enter search mode (activated by '/' key)
     fn enter_search_mode(&mut self) {
         if self.search_mode == SearchMode::None {
@@ -245,6 +997,19 @@ impl AppState {
         }
     }
 
+    /// Close the sort/filter menu, re-scanning the tree with its settings
+    fn close_options_menu(
+        &mut self,
+        args: &mut InteractiveArgs,
+        root_path: &Path,
+    ) -> anyhow::Result<()> {
+        if let Some(menu) = self.options_menu.take() {
+            menu.apply(args);
+            self.refresh(args, root_path)?;
+        }
+        Ok(())
+    }
+
     /// Check if currently in any search/filter mode
     fn in_search_mode(&self) -> bool {
         self.search_mode != SearchMode::None
@@ -279,7 +1044,7 @@ impl AppState {
                 .filter(|entry| {
                     entry.path
                         .file_name()
-                        .and_then(|name| name.to_str())
+                        .map(utils::display_os_str)
                         .map(|name| name.to_lowercase().contains(&query_lower))
                         .unwrap_or(false)
                 })
@@ -307,9 +1072,17 @@ pub fn run(args: &InteractiveArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
     }
     let root_path = fs::canonicalize(&args.path)?;
 
-    let mut app_state = AppState::new(args, &root_path)?;
+    // Keep a mutable copy so settings can be changed at runtime from the menu.
+    let mut args = args.clone();
+    let mut app_state = AppState::new(&args, &root_path)?;
+    let watcher = if args.watch {
+        Some(watch::FsWatcher::new(&root_path, Duration::from_millis(args.debounce))?)
+    } else {
+        None
+    };
     let mut terminal = setup_terminal()?;
-    let post_exit_action = run_app(&mut terminal, &mut app_state, args, ls_colors)?;
+    let post_exit_action =
+        run_app(&mut terminal, &mut app_state, &mut args, &root_path, watcher.as_ref(), ls_colors)?;
     restore_terminal(&mut terminal)?;
 
     match post_exit_action {
@@ -324,7 +1097,16 @@ pub fn run(args: &InteractiveArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
             Command::new(editor).arg(path).status()?;
         }
         PostExitAction::PrintPath(path) => {
-            println!("{}", path.display());
+            // Raw bytes, so a path that isn't valid UTF-8 still works when passed to a shell.
+            let mut out = stdout().lock();
+            #[cfg(unix)]
+            {
+                use std::os::unix::ffi::OsStrExt;
+                out.write_all(path.as_os_str().as_bytes())?;
+            }
+            #[cfg(not(unix))]
+            write!(out, "{}", path.display())?;
+            writeln!(out)?;
         }
         PostExitAction::None => {}
     }
@@ -335,33 +1117,108 @@ pub fn run(args: &InteractiveArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
 fn run_app<B: Backend + Write>(
     terminal: &mut Terminal<B>,
     app_state: &mut AppState,
-    args: &InteractiveArgs,
+    args: &mut InteractiveArgs,
+    root_path: &Path,
+    watcher: Option<&watch::FsWatcher>,
     ls_colors: &LsColors,
 ) -> anyhow::Result<PostExitAction> {
     loop {
         terminal.draw(|f| ui(f, app_state, args, ls_colors))?;
 
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
+        // In watch mode, wake up periodically to pick up filesystem changes.
+        if let Some(watcher) = watcher {
+            if !event::poll(WATCH_POLL_INTERVAL)? {
+                if watcher.poll_change() {
+                    app_state.refresh(args, root_path)?;
+                }
+                continue;
+            }
+        }
+
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                app_state.command_message = None;
+                // Any key dismisses the help overlay.
+                if app_state.show_help {
+                    app_state.show_help = false;
+                    continue;
+                }
+                if let Some(finder) = app_state.fuzzy_finder.as_mut() {
+                    match key.code {
+                        KeyCode::Esc => app_state.fuzzy_finder = None,
+                        KeyCode::Enter => app_state.jump_to_fuzzy_match(),
+                        KeyCode::Down => finder.next(),
+                        KeyCode::Up => finder.previous(),
+                        KeyCode::Backspace => finder.pop(&app_state.master_entries),
+                        KeyCode::Char(c) => finder.push(c, &app_state.master_entries),
+                        _ => {}
+                    }
+                    continue;
+                }
+                if let Some(line) = app_state.command_line.as_mut() {
+                    match key.code {
+                        KeyCode::Esc => app_state.command_line = None,
+                        KeyCode::Enter => app_state.run_command_line(args, root_path)?,
+                        KeyCode::Tab => app_state.complete_command_line(),
+                        // Deleting past the start closes the command line, as in Vim.
+                        KeyCode::Backspace if line.pop().is_none() => {
+                            app_state.command_line = None;
+                        }
+                        KeyCode::Backspace => {}
+                        KeyCode::Char(c) => line.push(c),
+                        _ => {}
+                    }
+                    continue;
+                }
+                if let Some(menu) = app_state.options_menu.as_mut() {
+                    match key.code {
+                        KeyCode::Down | KeyCode::Char('j') => menu.next(),
+                        KeyCode::Up | KeyCode::Char('k') => menu.previous(),
+                        KeyCode::Enter | KeyCode::Char(' ') => menu.change_selected(),
+                        KeyCode::Esc | KeyCode::Char('F') | KeyCode::Char('q') => {
+                            app_state.close_options_menu(args, root_path)?;
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
                 match key.code {
                     KeyCode::Char('s') if key.modifiers == KeyModifiers::CONTROL => {
                         if let Some(entry) = app_state.get_selected_entry() {
                             break Ok(PostExitAction::PrintPath(entry.path.clone()));
                         }
                     }
+                    KeyCode::Char('f') if key.modifiers == KeyModifiers::CONTROL => {
+                        app_state.open_fuzzy_finder();
+                    }
                     KeyCode::Char('q') => {
-                        break Ok(PostExitAction::None);
+                        break Ok(quit_action(app_state, args));
                     }
                     KeyCode::Esc => {
                         if app_state.in_search_mode() {
                             app_state.exit_search_mode();
                         } else {
-                            break Ok(PostExitAction::None);
+                            break Ok(quit_action(app_state, args));
                         }
                     }
+                    KeyCode::Char('d') if !app_state.in_search_mode() => {
+                        app_state.toggle_dirs_only(args, root_path)?;
+                    }
+                    KeyCode::Char('r') if !app_state.in_search_mode() => {
+                        app_state.toggle_recent_view();
+                    }
+                    KeyCode::Char('?') if !app_state.in_search_mode() => {
+                        app_state.show_help = true;
+                    }
+                    KeyCode::Char('F') if !app_state.in_search_mode() => {
+                        app_state.options_menu = Some(OptionsMenu::new(args));
+                    }
                     KeyCode::Char('/') if !app_state.in_search_mode() => {
                         app_state.enter_search_mode();
                     }
+                    KeyCode::Char(':') if !app_state.in_search_mode() => {
+                        app_state.command_line = Some(String::new());
+                    }
                     KeyCode::Backspace if app_state.in_search_mode() => {
                         app_state.remove_from_query();
                     }
@@ -370,9 +1227,21 @@ fn run_app<B: Backend + Write>(
                     }
                     KeyCode::Down | KeyCode::Char('j') => app_state.next(),
                     KeyCode::Up | KeyCode::Char('k') => app_state.previous(),
+                    KeyCode::Left | KeyCode::Char('h') if args.navigate => {
+                        app_state.go_to_parent(args, root_path)?;
+                    }
+                    KeyCode::Right | KeyCode::Char('l') if args.navigate => {
+                        app_state.enter_selected_directory(args, root_path)?;
+                    }
+                    KeyCode::Left | KeyCode::Char('h') => app_state.scroll_left(),
+                    KeyCode::Right | KeyCode::Char('l') => app_state.scroll_right(),
+                    KeyCode::Backspace => app_state.unfocus(args, root_path)?,
                     KeyCode::Enter => {
                         if let Some(entry) = app_state.get_selected_entry() {
-                            if entry.is_dir {
+                            if entry.is_dir && args.focus {
+                                app_state.focus_selected_directory(args)?;
+                            } else if entry.is_dir {
+                                app_state.load_selected_children(args)?;
                                 app_state.toggle_selected_directory();
                             } else {
                                 break Ok(PostExitAction::OpenFile(entry.path.clone()));
@@ -382,27 +1251,74 @@ fn run_app<B: Backend + Write>(
                     _ => {}
                 }
             }
+            // Redraw right away instead of waiting for the next key.
+            Event::Resize(_, height) => app_state.handle_resize(height),
+            Event::Mouse(mouse) if !app_state.overlay_open() => {
+                app_state.handle_mouse(mouse, args)?;
+            }
+            _ => {}
+        }
+
+        if args.follow_selected {
+            app_state.center_selection();
         }
     }
 }
 
+/// Completes the last word of a command line: the command's name, or the argument of `sort`
+/// and `toggle`. Returns the completed line and the candidates that matched; several
+/// candidates complete as far as they agree.
+fn complete_command(line: &str) -> (String, Vec<String>) {
+    let (head, word, candidates): (&str, &str, Vec<String>) = match line.split_once(' ') {
+        None => ("", line, COMMANDS.iter().map(|c| c.to_string()).collect()),
+        Some(("sort", word)) => {
+            ("sort ", word, SortType::value_variants().iter().map(ToString::to_string).collect())
+        }
+        Some(("toggle", word)) => {
+            ("toggle ", word, TOGGLE_SETTINGS.iter().map(|s| s.to_string()).collect())
+        }
+        Some(_) => return (line.to_string(), Vec::new()),
+    };
+    let matches: Vec<String> = candidates.into_iter().filter(|c| c.starts_with(word)).collect();
+    let completed = match matches.as_slice() {
+        [] => word.to_string(),
+        [only] => format!("{only} "),
+        [first, rest @ ..] => rest.iter().fold(first.clone(), |prefix, candidate| {
+            prefix
+                .chars()
+                .zip(candidate.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        }),
+    };
+    (format!("{head}{completed}"), matches)
+}
+
+/// Returns the action for a normal quit, which prints the selection with `--print-on-exit`.
+fn quit_action(app_state: &AppState, args: &InteractiveArgs) -> PostExitAction {
+    match app_state.get_selected_entry() {
+        Some(entry) if args.print_on_exit => PostExitAction::PrintPath(entry.path.clone()),
+        _ => PostExitAction::None,
+    }
+}
+
 fn ui(f: &mut Frame, app_state: &mut AppState, args: &InteractiveArgs, ls_colors: &LsColors) {
     let frame_width = f.size().width as usize;
-    let items: Vec<ListItem> = app_state
+    let now = SystemTime::now();
+    let palette = Palette::for_theme(args.theme);
+    let recent_view = app_state.recent_view;
+    let lines: Vec<Vec<Span>> = app_state
         .visible_entries
         .iter()
         .map(|entry| {
             let mut spans = Vec::new();
             if args.git_status {
                 let (status_char, status_color) = if let Some(status) = entry.git_status {
-                    let color = match status {
-                        git::FileStatus::New | git::FileStatus::Renamed => Color::Green,
-                        git::FileStatus::Modified | git::FileStatus::Typechange => Color::Yellow,
-                        git::FileStatus::Deleted => Color::Red,
-                        git::FileStatus::Conflicted => Color::LightRed,
-                        git::FileStatus::Untracked => Color::Magenta,
-                    };
-                    (status.get_char().to_string(), color)
+                    (status.get_char().to_string(), palette.git_status(status))
+                } else if entry.contains_changes {
+                    // Worth expanding: something inside has changed.
+                    ("*".to_string(), palette.git_modified)
                 } else {
                     (" ".to_string(), Color::Reset)
                 };
@@ -415,45 +1331,84 @@ fn ui(f: &mut Frame, app_state: &mut AppState, args: &InteractiveArgs, ls_colors
                 let perms_str = entry.permissions.as_deref().unwrap_or("----------");
                 spans.push(Span::styled(
                     format!("{perms_str} "),
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(palette.muted),
                 ));
             }
-            let indent_str = "    ".repeat(entry.depth.saturating_sub(1));
-            spans.push(Span::raw(indent_str));
-            let branch_str = if entry.is_dir {
-                if entry.is_expanded {
-                    "▼ "
+            // The flat list has no nesting to show.
+            if !recent_view {
+                let indent_str = "    ".repeat(entry.depth.saturating_sub(1));
+                spans.push(Span::raw(indent_str));
+                let branch_str = if entry.is_dir {
+                    if entry.is_expanded {
+                        "▼ "
+                    } else {
+                        "▶ "
+                    }
+                } else {
+                    "  "
+                };
+                spans.push(Span::raw(branch_str));
+            }
+            if args.icons.is_some_and(|mode| mode.shows(entry.is_dir)) {
+                if !entry.is_dir && icons::is_excluded(&entry.path, &args.no_icons_for) {
+                    spans.push(Span::raw("  "));
                 } else {
-                    "▶ "
+                    let (icon, color) = icons::get_icon_for_path(&entry.path, entry.is_dir);
+                    let style = Style::default().fg(map_color(color));
+                    spans.push(Span::styled(format!("{icon} "), style));
                 }
-            } else {
-                "  "
-            };
-            spans.push(Span::raw(branch_str));
-            if args.icons {
-                let (icon, color) = icons::get_icon_for_path(&entry.path, entry.is_dir);
-                spans.push(Span::styled(format!("{icon} "), Style::default().fg(map_color(color))));
             }
 
-            let name = entry.path.file_name().unwrap().to_string_lossy();
+            let name = if recent_view {
+                relative_path(entry).into()
+            } else {
+                utils::display_os_str(entry.path.file_name().unwrap_or_default())
+            };
             let lscolors_style = ls_colors.style_for_path(&entry.path).cloned().unwrap_or_default();
-            let ratatui_style = to_ratatui_style(lscolors_style);
+            let mut ratatui_style = to_ratatui_style(lscolors_style);
+            if ratatui_style.fg.is_none() {
+                if entry.is_dir {
+                    ratatui_style = ratatui_style.fg(palette.directory);
+                } else if let Some(color) = entry.kind.default_color() {
+                    ratatui_style = ratatui_style.fg(map_color(color));
+                }
+            }
             let name_span = Span::styled(name.to_string(), ratatui_style);
             spans.push(name_span);
+            if args.classify {
+                spans.push(Span::raw(entry.kind.classify_suffix(entry.executable)));
+            }
 
-            if args.size && !entry.is_dir {
-                if let Some(size) = entry.size {
-                    let size_str = utils::format_size(size);
-                    let left_len: usize = spans.iter().map(|s| s.width()).sum();
-                    let padding =
-                        frame_width.saturating_sub(left_len).saturating_sub(size_str.len());
-                    spans.push(Span::raw(" ".repeat(padding)));
-                    spans.push(Span::styled(size_str, Style::default().fg(Color::DarkGray)));
-                }
+            // Right-aligned columns: the modification time, then the size.
+            let mut right_columns = Vec::new();
+            if let Some(modified) = entry.modified.filter(|_| args.time || recent_view) {
+                right_columns.push(format!("{:>4}", utils::format_relative_time(modified, now)));
+            }
+            if let Some(size) = entry.size.filter(|_| args.size && !entry.is_dir) {
+                right_columns.push(utils::display_size(size, args.bytes));
+            }
+            if !right_columns.is_empty() {
+                let right_str = right_columns.join(" ");
+                let left_len: usize = spans.iter().map(|s| s.width()).sum();
+                let padding = frame_width.saturating_sub(left_len).saturating_sub(right_str.len());
+                spans.push(Span::raw(" ".repeat(padding)));
+                spans.push(Span::styled(right_str, Style::default().fg(palette.muted)));
             }
-            ListItem::new(Line::from(spans))
+            spans
         })
         .collect();
+
+    // Clamp the horizontal offset so we can't scroll past the longest line.
+    let list_width = frame_width.saturating_sub(HIGHLIGHT_SYMBOL.len());
+    let max_line_width =
+        lines.iter().map(|spans| spans.iter().map(|s| s.width()).sum::<usize>()).max().unwrap_or(0);
+    app_state.horizontal_offset =
+        app_state.horizontal_offset.min(max_line_width.saturating_sub(list_width));
+    let items: Vec<ListItem> = lines
+        .into_iter()
+        .map(|spans| ListItem::new(Line::from(scroll_spans(spans, app_state.horizontal_offset))))
+        .collect();
+
     // Create layout: main area for list + bottom line for status
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -463,63 +1418,302 @@ fn ui(f: &mut Frame, app_state: &mut AppState, args: &InteractiveArgs, ls_colors
         ])
         .split(f.size());
 
+    app_state.viewport_height = chunks[0].height as usize;
+    app_state.list_area = chunks[0];
+
     // Render the file list in the main area
     let list = List::new(items)
         .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
-        .highlight_symbol("> ");
+        .highlight_symbol(HIGHLIGHT_SYMBOL);
     f.render_stateful_widget(list, chunks[0], &mut app_state.list_state);
 
     // Create and render status line
-    let status_text = if app_state.in_search_mode() {
+    let status_text = if let Some(line) = &app_state.command_line {
+        format!(":{line}")
+    } else if let Some(message) = &app_state.command_message {
+        message.clone()
+    } else if app_state.in_search_mode() {
         let match_count = app_state.visible_entries.len();
         format!("Search: {} ({} matches)", app_state.search_query, match_count)
+    } else if recent_view {
+        "Recently modified files (r to return to the tree), ? for help, q to quit".to_string()
+    } else if let Some(root) = app_state.root_stack.last() {
+        // Show where we are after entering a directory in --navigate mode
+        format!("In {} (h/← to go back), ? for help, q to quit", root.display())
+    } else if let Some(root) = &app_state.focus_root {
+        format!("Focused on {} (Backspace to go back), ? for help, q to quit", root.display())
     } else {
         // Show help text when not searching
-        "Press / to search, q to quit".to_string()
+        "Press / to search, F for sort/filter options, : for commands, ? for help, q to quit"
+            .to_string()
     };
 
-    let status_paragraph = Paragraph::new(status_text)
-        .style(if app_state.in_search_mode() { 
-            Style::default().fg(Color::Yellow) 
-        } else { 
-            Style::default().fg(Color::Gray) 
-        });
+    let typing = app_state.in_search_mode() || app_state.command_line.is_some();
+    let status_paragraph = Paragraph::new(status_text).style(if typing {
+        Style::default().fg(palette.accent)
+    } else {
+        Style::default().fg(palette.status)
+    });
     f.render_widget(status_paragraph, chunks[1]);
+
+    if let Some(menu) = &app_state.options_menu {
+        render_options_menu(f, menu, &palette);
+    }
+    if let Some(finder) = &app_state.fuzzy_finder {
+        render_fuzzy_finder(f, finder, &app_state.master_entries, &palette);
+    }
+    if app_state.show_help {
+        render_help(f, &palette);
+    }
+}
+
+/// Draws the fuzzy finder as a centered overlay: the query, then the best matches.
+fn render_fuzzy_finder(
+    f: &mut Frame,
+    finder: &FuzzyFinder,
+    entries: &[FileEntry],
+    palette: &Palette,
+) {
+    let area = centered_rect(f.size().width * 3 / 4, f.size().height * 3 / 4, f.size());
+    let visible_rows = area.height.saturating_sub(4) as usize;
+    // Scroll the results so the highlighted match stays in view.
+    let first = finder.selected.saturating_sub(visible_rows.saturating_sub(1));
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("> ", Style::default().fg(palette.accent)),
+            Span::raw(finder.query.clone()),
+        ]),
+        Line::from(Span::styled(
+            format!("{} matches", finder.results.len()),
+            Style::default().fg(palette.muted),
+        )),
+    ];
+    lines.extend(finder.results.iter().enumerate().skip(first).take(visible_rows).map(
+        |(position, &i)| {
+            let style = if position == finder.selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(relative_path(&entries[i]), style))
+        },
+    ));
+
+    let block = Block::default().borders(Borders::ALL).title(" Find (Enter: jump, Esc: close) ");
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Returns the entry's path relative to the tree root, using its depth to trim the prefix.
+fn relative_path(entry: &FileEntry) -> String {
+    let components: Vec<_> = entry.path.components().collect();
+    let start = components.len().saturating_sub(entry.depth);
+    let relative: PathBuf = components[start..].iter().collect();
+    utils::display_os_str(relative.as_os_str()).into_owned()
+}
+
+/// Draws the key binding table as a centered overlay on top of the list.
+fn render_help(f: &mut Frame, palette: &Palette) {
+    let key_width = KEY_BINDINGS.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+    let mut lines: Vec<Line> = KEY_BINDINGS
+        .iter()
+        .map(|(key, action)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{key:<key_width$}  "),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(*action),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Press any key to close",
+        Style::default().fg(palette.muted),
+    )));
+
+    let width = lines.iter().map(|line| line.width()).max().unwrap_or(0) as u16 + 4;
+    let area = centered_rect(width, lines.len() as u16 + 2, f.size());
+    let block = Block::default().borders(Borders::ALL).title(" Keys ");
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Draws the sort/filter menu as a centered overlay on top of the list.
+fn render_options_menu(f: &mut Frame, menu: &OptionsMenu, palette: &Palette) {
+    let mut lines: Vec<Line> = MENU_ITEMS
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let style = if i == menu.selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(menu.label(*item), style))
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter: change  F/Esc: apply",
+        Style::default().fg(palette.muted),
+    )));
+
+    let area = centered_rect(36, lines.len() as u16 + 2, f.size());
+    let block = Block::default().borders(Borders::ALL).title(" Sort & filter ");
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Returns a rectangle of the given size centered within `area`, clamped to fit.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height)
+}
+
+/// Drops the first `offset` columns from a line's spans to scroll it horizontally.
+///
+/// Columns are counted by display width, as the clamp on the offset is. A wide character
+/// cut in half by the edge leaves a blank column in its place.
+fn scroll_spans(spans: Vec<Span<'static>>, offset: usize) -> Vec<Span<'static>> {
+    let mut remaining = offset;
+    spans
+        .into_iter()
+        .filter_map(|span| {
+            if remaining == 0 {
+                return Some(span);
+            }
+            let width = span.width();
+            if width <= remaining {
+                remaining -= width;
+                return None;
+            }
+            let mut content = String::new();
+            for c in span.content.chars() {
+                let char_width = c.width().unwrap_or(0);
+                if remaining == 0 {
+                    content.push(c);
+                } else if char_width <= remaining {
+                    remaining -= char_width;
+                } else {
+                    content.push_str(&" ".repeat(char_width - remaining));
+                    remaining = 0;
+                }
+            }
+            Some(Span::styled(content, span.style))
+        })
+        .collect()
+}
+
+/// Loads the git status for `root_path` when `-G` is set.
+fn load_git_status(
+    args: &InteractiveArgs,
+    root_path: &Path,
+) -> anyhow::Result<Option<GitRepoStatus>> {
+    if args.git_status {
+        git::load_status(root_path, args.git_submodules == GitSubmodules::Show)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns how deep the tree is scanned up front: all the way, or with --lazy only as far
+/// as --expand-level opens it. Deeper directories are scanned when first expanded. With
+/// --expand-match the matches may lie at any depth, so the whole tree is scanned.
+fn scan_depth(args: &InteractiveArgs) -> Option<usize> {
+    let lazy = args.lazy && args.expand_match.is_empty();
+    lazy.then(|| args.expand_level.unwrap_or(1).max(1))
 }
 
+/// Scans the tree under `path`, down to `max_depth` levels when given.
 fn scan_directory(
     path: &Path,
-    status_info: Option<(&StatusCache, &PathBuf)>,
+    max_depth: Option<usize>,
+    git_repo_status: Option<&GitRepoStatus>,
     args: &InteractiveArgs,
 ) -> anyhow::Result<Vec<FileEntry>> {
+    let sort_options = args.to_sort_options();
     let mut builder = WalkBuilder::new(path);
-    builder.hidden(!args.all).git_ignore(args.gitignore);
+    builder
+        .hidden(!args.all)
+        .git_ignore(args.gitignore)
+        .same_file_system(args.one_filesystem)
+        // Child counts need the level below the deepest one scanned.
+        .max_depth(max_depth.map(|max| max + usize::from(sort_options.counts_children())));
+    builder.add_custom_ignore_filename(utils::LSTR_IGNORE_FILENAME);
+    if let Some(status) = git_repo_status {
+        let opaque_dirs = status.opaque_dirs_under(path, path);
+        if !opaque_dirs.is_empty() {
+            // Submodules are listed but not descended into.
+            builder.filter_entry(move |entry| {
+                !entry.path().parent().is_some_and(|parent| opaque_dirs.iter().any(|d| d == parent))
+            });
+        }
+    }
 
     // Collect all DirEntry objects first, filtering out the root path
-    let mut dir_entries: Vec<_> =
-        builder.build().flatten().filter(|result| result.path() != path).collect();
+    let mut dir_entries: Vec<_> = builder
+        .build()
+        .flatten()
+        .filter(|result| result.path() != path)
+        .filter(|result| !args.dirs_only || result.file_type().is_some_and(|ft| ft.is_dir()))
+        .collect();
+    let child_counts = if sort_options.counts_children() {
+        sort::count_children(&dir_entries)
+    } else {
+        sort::ChildCounts::new()
+    };
+    if let Some(max) = max_depth {
+        dir_entries.retain(|entry| entry.depth() <= max);
+    }
+
+    // Read once, for both sorting and the columns.
+    let mut metadata_cache = if args.size
+        || args.permissions
+        || args.time
+        || args.classify
+        || sort_options.reads_metadata()
+    {
+        utils::read_metadata(&dir_entries, args.dereference)
+    } else {
+        utils::MetadataCache::new()
+    };
 
     // Apply tree-aware sorting to preserve parent-child relationships
-    let sort_options = args.to_sort_options();
-    sort::sort_entries_hierarchically(&mut dir_entries, &sort_options);
+    sort::sort_entries_hierarchically(
+        &mut dir_entries,
+        &sort_options,
+        &metadata_cache,
+        &child_counts,
+    );
 
     // Convert DirEntry objects to FileEntry objects
     let mut entries = Vec::new();
     for result in dir_entries {
-        let metadata = if args.size || args.permissions { result.metadata().ok() } else { None };
+        let metadata = metadata_cache.remove(result.path());
         let is_dir = result.file_type().is_some_and(|ft| ft.is_dir());
-        let git_status = if let Some((cache, root)) = status_info {
-            result.path().strip_prefix(root).ok().and_then(|rel_path| cache.get(rel_path)).copied()
-        } else {
-            None
-        };
+        let rel_path =
+            git_repo_status.and_then(|status| result.path().strip_prefix(&status.root).ok());
+        let git_status = git_repo_status
+            .zip(rel_path)
+            .and_then(|(status, rel_path)| status.cache.get(rel_path))
+            .map(|entry| entry.status);
+        let contains_changes =
+            is_dir && git_repo_status.zip(rel_path).is_some_and(|(s, p)| s.dirty_dirs.contains(p));
+        let kind = result.file_type().map_or(utils::FileKind::File, utils::FileKind::of);
+        let executable = metadata.as_ref().is_some_and(utils::is_executable);
         let size = if args.size && !is_dir { metadata.as_ref().map(|m| m.len()) } else { None };
+        let modified =
+            if args.time { metadata.as_ref().and_then(|m| m.modified().ok()) } else { None };
         let permissions = if args.permissions {
             metadata.map(|_md| {
                 #[cfg(unix)]
                 {
                     let mode = _md.permissions().mode();
-                    let file_type_char = if _md.is_dir() { 'd' } else { '-' };
+                    let file_type_char = utils::FileKind::of(_md.file_type()).type_char();
                     format!("{}{}", file_type_char, utils::format_permissions(mode))
                 }
                 #[cfg(not(unix))]
@@ -538,6 +1732,12 @@ fn scan_directory(
             size,
             permissions,
             git_status,
+            contains_changes,
+            modified,
+            kind,
+            executable,
+            // Entries at the depth limit are the only ones whose children weren't scanned.
+            children_loaded: max_depth.is_none_or(|max| result.depth() < max),
         });
     }
     Ok(entries)
@@ -597,6 +1797,11 @@ mod tests {
                 size: None,
                 permissions: Some("drwxr-xr-x".to_string()),
                 git_status: None,
+                contains_changes: true,
+                modified: None,
+                kind: utils::FileKind::Directory,
+                executable: false,
+                children_loaded: true,
             },
             FileEntry {
                 path: PathBuf::from("src/main.rs"),
@@ -606,6 +1811,11 @@ mod tests {
                 size: Some(1024),
                 permissions: Some("-rw-r--r--".to_string()),
                 git_status: Some(git::FileStatus::Modified),
+                contains_changes: false,
+                modified: None,
+                kind: utils::FileKind::File,
+                executable: false,
+                children_loaded: true,
             },
             FileEntry {
                 path: PathBuf::from("README.md"),
@@ -615,6 +1825,11 @@ mod tests {
                 size: Some(512),
                 permissions: Some("-rw-r--r--".to_string()),
                 git_status: None,
+                contains_changes: false,
+                modified: None,
+                kind: utils::FileKind::File,
+                executable: false,
+                children_loaded: true,
             },
         ];
         let mut app_state = AppState {
@@ -624,6 +1839,19 @@ mod tests {
             search_mode: SearchMode::None,
             search_query: String::new(),
             original_visible_entries: Vec::new(),
+            horizontal_offset: 0,
+            viewport_height: 0,
+            list_area: Rect::default(),
+            options_menu: None,
+            show_help: false,
+            root_stack: Vec::new(),
+            focus_root: None,
+            outer: None,
+            fuzzy_finder: None,
+            git_repo_status: None,
+            recent_view: false,
+            command_line: None,
+            command_message: None,
         };
         app_state.regenerate_visible_entries();
         app_state.list_state.select(Some(0));
@@ -661,4 +1889,338 @@ mod tests {
         assert!(selected.is_some());
         assert_eq!(selected.unwrap().path, PathBuf::from("README.md"));
     }
+    #[test]
+    fn test_horizontal_scroll() {
+        let mut app_state = setup_test_app_state();
+        app_state.scroll_left();
+        assert_eq!(app_state.horizontal_offset, 0);
+        app_state.scroll_right();
+        assert_eq!(app_state.horizontal_offset, HORIZONTAL_SCROLL_STEP);
+        app_state.scroll_left();
+        assert_eq!(app_state.horizontal_offset, 0);
+    }
+    #[test]
+    fn test_scroll_spans() {
+        let spans = vec![Span::raw("ab"), Span::raw("cdef")];
+        let scrolled = scroll_spans(spans, 3);
+        assert_eq!(scrolled.len(), 1);
+        assert_eq!(scrolled[0].content, "def");
+        // Each CJK character takes two columns.
+        let scrolled = scroll_spans(vec![Span::raw("日本"), Span::raw("語x")], 4);
+        assert_eq!(scrolled[0].content, "語x");
+        let scrolled = scroll_spans(vec![Span::raw("日本語")], 3);
+        assert_eq!(scrolled[0].content, " 語");
+    }
+    #[test]
+    fn test_center_selection() {
+        let mut app_state = setup_test_app_state();
+        app_state.toggle_selected_directory();
+        assert_eq!(app_state.visible_entries.len(), 3);
+        app_state.viewport_height = 2;
+        app_state.list_state.select(Some(2));
+        app_state.center_selection();
+        assert_eq!(app_state.list_state.offset(), 1);
+        app_state.list_state.select(Some(0));
+        app_state.center_selection();
+        assert_eq!(app_state.list_state.offset(), 0);
+    }
+    #[test]
+    fn test_handle_resize() {
+        let mut app_state = setup_test_app_state();
+        app_state.toggle_selected_directory();
+        app_state.viewport_height = 1;
+        *app_state.list_state.offset_mut() = 2;
+        app_state.handle_resize(3);
+        assert_eq!(app_state.viewport_height, 2);
+        assert_eq!(app_state.list_state.offset(), 1);
+    }
+    #[test]
+    fn test_options_menu() {
+        let mut menu = OptionsMenu {
+            selected: 0,
+            sort: SortType::Name,
+            dirs_first: false,
+            all: false,
+            gitignore: false,
+        };
+        menu.change_selected();
+        assert_eq!(menu.sort, SortType::Size);
+        menu.previous();
+        assert_eq!(MENU_ITEMS[menu.selected], MenuItem::Gitignore);
+        menu.change_selected();
+        assert!(menu.gitignore);
+        menu.next();
+        menu.next();
+        menu.change_selected();
+        assert!(menu.dirs_first);
+    }
+    #[test]
+    fn test_quit_action() {
+        use clap::Parser;
+        let app_state = setup_test_app_state();
+        let args = InteractiveArgs::parse_from(["interactive"]);
+        assert!(matches!(quit_action(&app_state, &args), PostExitAction::None));
+        let args = InteractiveArgs::parse_from(["interactive", "--print-on-exit"]);
+        assert!(matches!(
+            quit_action(&app_state, &args),
+            PostExitAction::PrintPath(path) if path == Path::new("src")
+        ));
+    }
+    #[test]
+    fn test_key_bindings_table() {
+        let keys: HashSet<&str> = KEY_BINDINGS.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys.len(), KEY_BINDINGS.len());
+        assert!(keys.contains("?"));
+    }
+    #[test]
+    fn test_navigate_enter_and_back() -> anyhow::Result<()> {
+        use clap::Parser;
+        let temp_dir = tempfile::tempdir()?;
+        let root = fs::canonicalize(temp_dir.path())?;
+        fs::create_dir(root.join("sub"))?;
+        fs::write(root.join("sub/inner.txt"), "")?;
+        fs::write(root.join("top.txt"), "")?;
+
+        let args = InteractiveArgs::parse_from(["interactive", "--navigate"]);
+        let mut app_state = AppState::new(&args, &root)?;
+        assert_eq!(app_state.get_selected_entry().unwrap().path, root.join("sub"));
+
+        app_state.enter_selected_directory(&args, &root)?;
+        assert_eq!(app_state.current_root(&root), root.join("sub"));
+        assert_eq!(app_state.visible_entries.len(), 1);
+        assert_eq!(app_state.visible_entries[0].path, root.join("sub/inner.txt"));
+        assert_eq!(app_state.visible_entries[0].depth, 1);
+
+        app_state.go_to_parent(&args, &root)?;
+        assert_eq!(app_state.current_root(&root), root);
+        assert_eq!(app_state.get_selected_entry().unwrap().path, root.join("sub"));
+        Ok(())
+    }
+    #[test]
+    fn test_focus_and_unfocus() -> anyhow::Result<()> {
+        use clap::Parser;
+        let temp_dir = tempfile::tempdir()?;
+        let root = fs::canonicalize(temp_dir.path())?;
+        fs::create_dir_all(root.join("sub/deep"))?;
+        fs::write(root.join("sub/inner.txt"), "")?;
+        fs::write(root.join("top.txt"), "")?;
+
+        let args = InteractiveArgs::parse_from(["interactive", "--focus", "--expand-level", "2"]);
+        let mut app_state = AppState::new(&args, &root)?;
+        assert_eq!(app_state.visible_entries.len(), 4);
+
+        app_state.focus_selected_directory(&args)?;
+        assert_eq!(app_state.current_root(&root), root.join("sub"));
+        assert_eq!(app_state.visible_entries[0].path, root.join("sub/deep"));
+        assert_eq!(app_state.visible_entries[0].depth, 1);
+
+        app_state.unfocus(&args, &root)?;
+        assert_eq!(app_state.current_root(&root), root);
+        assert!(app_state.outer.is_none());
+        // The outer tree comes back as it was left, still expanded.
+        assert_eq!(app_state.get_selected_entry().unwrap().path, root.join("sub"));
+        assert_eq!(app_state.visible_entries.len(), 4);
+        Ok(())
+    }
+    #[test]
+    fn test_lazy_loading() -> anyhow::Result<()> {
+        use clap::Parser;
+        let temp_dir = tempfile::tempdir()?;
+        let root = fs::canonicalize(temp_dir.path())?;
+        fs::create_dir_all(root.join("sub/deep"))?;
+        fs::write(root.join("sub/deep/leaf.txt"), "")?;
+        fs::write(root.join("top.txt"), "")?;
+
+        let args = InteractiveArgs::parse_from(["interactive", "--lazy"]);
+        let mut app_state = AppState::new(&args, &root)?;
+        assert_eq!(app_state.master_entries.len(), 2);
+        assert!(!app_state.master_entries[0].children_loaded);
+
+        app_state.load_selected_children(&args)?;
+        app_state.toggle_selected_directory();
+        let paths: Vec<_> = app_state.visible_entries.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(paths, vec![root.join("sub"), root.join("sub/deep"), root.join("top.txt")]);
+        assert_eq!(app_state.visible_entries[1].depth, 2);
+        assert!(!app_state.visible_entries[1].children_loaded);
+
+        // A refresh scans the loaded directories again, but no others.
+        fs::write(root.join("sub/new.txt"), "")?;
+        app_state.refresh(&args, &root)?;
+        assert_eq!(app_state.visible_entries.len(), 4);
+        assert_eq!(app_state.master_entries.len(), 4);
+        Ok(())
+    }
+    #[test]
+    fn test_toggle_recent_view() {
+        let mut app_state = setup_test_app_state();
+        app_state.master_entries[1].modified = Some(SystemTime::UNIX_EPOCH);
+        app_state.master_entries[2].modified =
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(60));
+
+        app_state.toggle_recent_view();
+        let paths: Vec<_> = app_state.visible_entries.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(paths, vec![PathBuf::from("README.md"), PathBuf::from("src/main.rs")]);
+        assert_eq!(app_state.list_state.selected(), Some(0));
+
+        // The selected file is revealed in the tree on the way back.
+        app_state.next();
+        app_state.toggle_recent_view();
+        assert!(!app_state.recent_view);
+        assert!(app_state.master_entries[0].is_expanded);
+        assert_eq!(app_state.get_selected_entry().unwrap().path, PathBuf::from("src/main.rs"));
+    }
+    #[test]
+    fn test_toggle_dirs_only() -> anyhow::Result<()> {
+        use clap::Parser;
+        let temp_dir = tempfile::tempdir()?;
+        let root = fs::canonicalize(temp_dir.path())?;
+        fs::create_dir(root.join("sub"))?;
+        fs::write(root.join("sub/inner.txt"), "")?;
+        fs::write(root.join("top.txt"), "")?;
+
+        let mut args = InteractiveArgs::parse_from(["interactive", "--expand-level", "2"]);
+        let mut app_state = AppState::new(&args, &root)?;
+        app_state.list_state.select(Some(1));
+        assert_eq!(app_state.get_selected_entry().unwrap().path, root.join("sub/inner.txt"));
+
+        app_state.toggle_dirs_only(&mut args, &root)?;
+        assert!(args.dirs_only);
+        assert!(app_state.visible_entries.iter().all(|e| e.is_dir));
+        assert_eq!(app_state.get_selected_entry().unwrap().path, root.join("sub"));
+
+        app_state.toggle_dirs_only(&mut args, &root)?;
+        assert_eq!(app_state.visible_entries.len(), 3);
+        Ok(())
+    }
+    #[test]
+    fn test_expand_match() -> anyhow::Result<()> {
+        use clap::Parser;
+        let temp_dir = tempfile::tempdir()?;
+        let root = fs::canonicalize(temp_dir.path())?;
+        fs::create_dir_all(root.join("app/src"))?;
+        fs::write(root.join("app/src/main.rs"), "")?;
+        fs::create_dir_all(root.join("docs/guide"))?;
+        fs::write(root.join("docs/guide/intro.md"), "")?;
+
+        let args = InteractiveArgs::parse_from(["interactive", "--expand-match", "*/src"]);
+        let app_state = AppState::new(&args, &root)?;
+        let visible: Vec<_> = app_state.visible_entries.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(
+            visible,
+            vec![
+                root.join("app"),
+                root.join("app/src"),
+                root.join("app/src/main.rs"),
+                root.join("docs")
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_expand_match_lazy() -> anyhow::Result<()> {
+        use clap::Parser;
+        let temp_dir = tempfile::tempdir()?;
+        let root = fs::canonicalize(temp_dir.path())?;
+        fs::create_dir_all(root.join("crates/core/src"))?;
+        fs::write(root.join("crates/core/src/lib.rs"), "")?;
+
+        let args =
+            InteractiveArgs::parse_from(["interactive", "--lazy", "--expand-match", "**/src"]);
+        let app_state = AppState::new(&args, &root)?;
+        let visible: Vec<_> = app_state.visible_entries.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(
+            visible,
+            vec![
+                root.join("crates"),
+                root.join("crates/core"),
+                root.join("crates/core/src"),
+                root.join("crates/core/src/lib.rs")
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_palette_for_theme() {
+        let default = Palette::for_theme(Theme::Default);
+        assert_eq!(default.muted, Color::DarkGray);
+        assert_eq!(default.git_status(git::FileStatus::Untracked), Color::Magenta);
+        // DarkGray is barely visible on a white background.
+        assert_ne!(Palette::for_theme(Theme::Light).muted, Color::DarkGray);
+    }
+    #[test]
+    fn test_fuzzy_finder_jump() {
+        let mut app_state = setup_test_app_state();
+        app_state.open_fuzzy_finder();
+        let finder = app_state.fuzzy_finder.as_mut().unwrap();
+        assert_eq!(finder.results.len(), 3);
+        for c in "mnrs".chars() {
+            finder.push(c, &app_state.master_entries);
+        }
+        assert_eq!(finder.results, vec![1]);
+
+        app_state.jump_to_fuzzy_match();
+        assert!(app_state.fuzzy_finder.is_none());
+        assert!(app_state.master_entries[0].is_expanded);
+        assert_eq!(app_state.get_selected_entry().unwrap().path, PathBuf::from("src/main.rs"));
+    }
+    #[test]
+    fn test_command_line() {
+        use clap::Parser;
+        let mut args = InteractiveArgs::parse_from(["interactive"]);
+        let mut app_state = setup_test_app_state();
+        let root = Path::new("");
+        let mut run = |app_state: &mut AppState, line: &str| {
+            app_state.command_line = Some(line.to_string());
+            app_state.run_command_line(&mut args, root).unwrap();
+        };
+
+        run(&mut app_state, "goto src/main.rs");
+        assert!(app_state.command_line.is_none());
+        assert_eq!(app_state.get_selected_entry().unwrap().path, PathBuf::from("src/main.rs"));
+        run(&mut app_state, "collapse-all");
+        assert_eq!(app_state.visible_entries.len(), 2);
+        assert_eq!(app_state.get_selected_entry().unwrap().path, PathBuf::from("src"));
+        run(&mut app_state, "expand-all");
+        assert_eq!(app_state.visible_entries.len(), 3);
+
+        run(&mut app_state, "goto missing.txt");
+        assert_eq!(app_state.command_message.as_deref(), Some("Not in the tree: missing.txt"));
+        run(&mut app_state, "explode");
+        assert_eq!(app_state.command_message.as_deref(), Some("Unknown command: explode"));
+    }
+    #[test]
+    fn test_complete_command() {
+        assert_eq!(complete_command("ex"), ("expand-all ".to_string(), vec!["expand-all".into()]));
+        assert_eq!(complete_command("sort mod").0, "sort modified ");
+        let (line, candidates) = complete_command("toggle dirs");
+        assert_eq!(line, "toggle dirs-");
+        assert_eq!(candidates, vec!["dirs-first", "dirs-last", "dirs-only"]);
+        assert_eq!(complete_command("goto sr").0, "goto sr");
+    }
+    #[test]
+    fn test_handle_mouse() -> anyhow::Result<()> {
+        use clap::Parser;
+        let args = InteractiveArgs::parse_from(["interactive"]);
+        let mut app_state = setup_test_app_state();
+        app_state.list_area = Rect::new(0, 0, 40, 10);
+        let mouse = |kind, row| MouseEvent { kind, column: 5, row, modifiers: KeyModifiers::NONE };
+        let click = MouseEventKind::Down(MouseButton::Left);
+
+        app_state.handle_mouse(mouse(click, 1), &args)?;
+        assert_eq!(app_state.get_selected_entry().unwrap().path, PathBuf::from("README.md"));
+        // Below the last entry, and outside the list, nothing happens.
+        app_state.handle_mouse(mouse(click, 5), &args)?;
+        app_state.handle_mouse(mouse(click, 12), &args)?;
+        assert_eq!(app_state.list_state.selected(), Some(1));
+        app_state.handle_mouse(mouse(MouseEventKind::ScrollDown, 0), &args)?;
+        assert_eq!(app_state.list_state.selected(), Some(1));
+
+        app_state.handle_mouse(mouse(click, 0), &args)?;
+        assert_eq!(app_state.visible_entries.len(), 2);
+        app_state.handle_mouse(mouse(click, 0), &args)?;
+        assert_eq!(app_state.visible_entries.len(), 3);
+        app_state.handle_mouse(mouse(MouseEventKind::ScrollDown, 0), &args)?;
+        assert_eq!(app_state.get_selected_entry().unwrap().path, PathBuf::from("src/main.rs"));
+        Ok(())
+    }
 }
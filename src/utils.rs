@@ -1,5 +1,18 @@
 //! Shared utility functions for the lstr application.
 
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::DirEntry;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::SystemTime;
+
+/// The name of lstr's own ignore file, which uses gitignore syntax.
+pub const LSTR_IGNORE_FILENAME: &str = ".lstrignore";
+
 // This entire module will only be compiled on Unix-like systems.
 
 /// Formats a size in bytes into a human-readable string using binary prefixes (KiB, MiB).
@@ -24,18 +37,285 @@ pub fn format_size(bytes: u64) -> String {
     }
 }
 
+/// Parses a size such as `512`, `100M`, or `1.5GiB`. Units are binary, as in
+/// [`format_size`], and may be written `K`, `KB`, or `KiB` in any case.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let unit_start = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let (number, unit) = s.split_at(unit_start);
+    let number: f64 = number.parse().map_err(|_| format!("invalid size '{s}'"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => return Err(format!("unknown size unit '{unit}' (expected B, K, M, G, or T)")),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Formats a size for display, either humanized or as a raw byte count.
+pub fn display_size(bytes: u64, raw_bytes: bool) -> String {
+    if raw_bytes {
+        bytes.to_string()
+    } else {
+        format_size(bytes)
+    }
+}
+
+/// Formats how long ago `time` was, relative to `now`, in a compact form such as
+/// `45s`, `2h`, or `3d`. Times in the future are shown as `now`.
+pub fn format_relative_time(time: SystemTime, now: SystemTime) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const WEEK: u64 = 7 * DAY;
+    const MONTH: u64 = 30 * DAY;
+    const YEAR: u64 = 365 * DAY;
+
+    let Ok(elapsed) = now.duration_since(time) else {
+        return "now".to_string();
+    };
+    let secs = elapsed.as_secs();
+    if secs < MINUTE {
+        format!("{secs}s")
+    } else if secs < HOUR {
+        format!("{}m", secs / MINUTE)
+    } else if secs < DAY {
+        format!("{}h", secs / HOUR)
+    } else if secs < WEEK {
+        format!("{}d", secs / DAY)
+    } else if secs < MONTH {
+        format!("{}w", secs / WEEK)
+    } else if secs < YEAR {
+        format!("{}mo", secs / MONTH)
+    } else {
+        format!("{}y", secs / YEAR)
+    }
+}
+
+/// Reads the metadata for a walked entry.
+///
+/// When `dereference` is set, symlinks are followed so the metadata describes the
+/// target. Broken links fall back to the link's own metadata.
+pub fn entry_metadata(entry: &DirEntry, dereference: bool) -> Option<fs::Metadata> {
+    if dereference {
+        if let Ok(md) = fs::metadata(entry.path()) {
+            return Some(md);
+        }
+    }
+    entry.metadata().ok()
+}
+
+/// Metadata read once per walked entry, keyed by path, so sorting and the columns that
+/// need it don't stat the same entry again.
+pub type MetadataCache = HashMap<PathBuf, fs::Metadata>;
+
+/// Reads the metadata of every entry, as [`entry_metadata`] does, into a cache. Entries
+/// whose metadata can't be read are left out.
+pub fn read_metadata(entries: &[DirEntry], dereference: bool) -> MetadataCache {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let metadata = entry_metadata(entry, dereference)?;
+            Some((entry.path().to_path_buf(), metadata))
+        })
+        .collect()
+}
+
+/// The kinds of entry that are told apart in the permissions column, by `--classify`,
+/// and by color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Directory,
+    Symlink,
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
+}
+
+impl FileKind {
+    /// Returns the kind of a file type. FIFOs, sockets, and devices only exist on Unix.
+    pub fn of(file_type: fs::FileType) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_fifo() {
+                return Self::Fifo;
+            } else if file_type.is_socket() {
+                return Self::Socket;
+            } else if file_type.is_block_device() {
+                return Self::BlockDevice;
+            } else if file_type.is_char_device() {
+                return Self::CharDevice;
+            }
+        }
+        if file_type.is_dir() {
+            Self::Directory
+        } else if file_type.is_symlink() {
+            Self::Symlink
+        } else {
+            Self::File
+        }
+    }
+
+    /// Returns the character that leads the permissions column, as in `ls -l`.
+    pub fn type_char(self) -> char {
+        match self {
+            Self::File => '-',
+            Self::Directory => 'd',
+            Self::Symlink => 'l',
+            Self::Fifo => 'p',
+            Self::Socket => 's',
+            Self::BlockDevice => 'b',
+            Self::CharDevice => 'c',
+        }
+    }
+
+    /// Returns the suffix `ls -F` appends to the name. Devices get none.
+    pub fn classify_suffix(self, executable: bool) -> &'static str {
+        match self {
+            Self::File if executable => "*",
+            Self::Directory => "/",
+            Self::Symlink => "@",
+            Self::Fifo => "|",
+            Self::Socket => "=",
+            Self::File | Self::BlockDevice | Self::CharDevice => "",
+        }
+    }
+
+    /// Returns the color for the special kinds when `LS_COLORS` doesn't give them one,
+    /// following the `dircolors` defaults.
+    pub fn default_color(self) -> Option<colored::Color> {
+        match self {
+            Self::Fifo => Some(colored::Color::Yellow),
+            Self::Socket => Some(colored::Color::Magenta),
+            Self::BlockDevice | Self::CharDevice => Some(colored::Color::BrightYellow),
+            Self::File | Self::Directory | Self::Symlink => None,
+        }
+    }
+}
+
+/// Returns whether any of the execute bits is set. Always false outside Unix.
+pub fn is_executable(metadata: &fs::Metadata) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        false
+    }
+}
+
+/// Runs `f` on every path, spreading the paths across threads, and collects the results
+/// by path. Paths for which `f` returns `None` are left out of the map.
+///
+/// This is for work that reads whole files, such as hashing or counting lines.
+pub fn map_files_parallel<T: Send>(
+    paths: &[&Path],
+    f: impl Fn(&Path) -> Option<T> + Sync,
+) -> HashMap<PathBuf, T> {
+    if paths.is_empty() {
+        return HashMap::new();
+    }
+    let workers = thread::available_parallelism().map_or(1, |n| n.get()).min(paths.len());
+    let chunk_size = paths.len().div_ceil(workers);
+    let f = &f;
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter_map(|path| Some((path.to_path_buf(), f(path)?)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap_or_default()).collect()
+    })
+}
+
+/// Converts a file name or path to text for display.
+///
+/// On Unix, names are arbitrary bytes. Any byte that is not part of valid UTF-8 is shown
+/// as `\xNN`, which flags the name and keeps distinct names apart, unlike the `�` that
+/// `to_string_lossy` puts in their place.
+pub fn display_os_str(s: &OsStr) -> Cow<'_, str> {
+    if let Some(s) = s.to_str() {
+        return Cow::Borrowed(s);
+    }
+    #[cfg(unix)]
+    {
+        use std::fmt::Write;
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut bytes = s.as_bytes();
+        let mut escaped = String::new();
+        loop {
+            match std::str::from_utf8(bytes) {
+                Ok(valid) => {
+                    escaped.push_str(valid);
+                    break;
+                }
+                Err(err) => {
+                    let (valid, rest) = bytes.split_at(err.valid_up_to());
+                    escaped.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    let invalid_len = err.error_len().unwrap_or(rest.len());
+                    for byte in &rest[..invalid_len] {
+                        _ = write!(escaped, "\\x{byte:02x}");
+                    }
+                    bytes = &rest[invalid_len..];
+                }
+            }
+        }
+        Cow::Owned(escaped)
+    }
+    #[cfg(not(unix))]
+    {
+        s.to_string_lossy()
+    }
+}
+
+/// Compiles `patterns` into one set. As in gitignore files, `*` and `?` stop at a `/`, while
+/// `**` crosses any number of directories.
+pub fn glob_set(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+    }
+    Ok(builder.build()?)
+}
+
 /// Formats a Unix file mode into a human-readable string (e.g., "rwxr-xr-x").
+///
+/// As in `ls -l`, the setuid, setgid, and sticky bits show in the execute positions as
+/// `s`/`t`, or `S`/`T` when the execute bit under them is not set.
 #[cfg(unix)]
 pub fn format_permissions(mode: u32) -> String {
+    let special = |execute: u32, bit: u32, set: char| match (mode & execute, mode & bit) {
+        (0, 0) => '-',
+        (_, 0) => 'x',
+        (0, _) => set.to_ascii_uppercase(),
+        _ => set,
+    };
     let user_r = if mode & 0o400 != 0 { 'r' } else { '-' };
     let user_w = if mode & 0o200 != 0 { 'w' } else { '-' };
-    let user_x = if mode & 0o100 != 0 { 'x' } else { '-' };
+    let user_x = special(0o100, 0o4000, 's');
     let group_r = if mode & 0o040 != 0 { 'r' } else { '-' };
     let group_w = if mode & 0o020 != 0 { 'w' } else { '-' };
-    let group_x = if mode & 0o010 != 0 { 'x' } else { '-' };
+    let group_x = special(0o010, 0o2000, 's');
     let other_r = if mode & 0o004 != 0 { 'r' } else { '-' };
     let other_w = if mode & 0o002 != 0 { 'w' } else { '-' };
-    let other_x = if mode & 0o001 != 0 { 'x' } else { '-' };
+    let other_x = special(0o001, 0o1000, 't');
     format!("{user_r}{user_w}{user_x}{group_r}{group_w}{group_x}{other_r}{other_w}{other_x}")
 }
 
@@ -44,6 +324,35 @@ pub fn format_permissions(mode: u32) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("100M"), Ok(100 * 1024 * 1024));
+        assert_eq!(parse_size("1.5kib"), Ok(1536));
+        assert_eq!(parse_size("2 GB"), Ok(2 * 1024 * 1024 * 1024));
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("M").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_kind_of_special_files() {
+        use std::os::unix::net::UnixListener;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let socket_path = temp_dir.path().join("socket");
+        let _listener = UnixListener::bind(&socket_path).unwrap();
+        let kind = FileKind::of(fs::symlink_metadata(&socket_path).unwrap().file_type());
+        assert_eq!(kind, FileKind::Socket);
+        assert_eq!((kind.type_char(), kind.classify_suffix(false)), ('s', "="));
+
+        let dev_null = FileKind::of(fs::metadata("/dev/null").unwrap().file_type());
+        assert_eq!(dev_null, FileKind::CharDevice);
+        assert_eq!((dev_null.type_char(), dev_null.classify_suffix(false)), ('c', ""));
+
+        let file = FileKind::of(fs::metadata(file!()).unwrap().file_type());
+        assert_eq!((file.classify_suffix(false), file.classify_suffix(true)), ("", "*"));
+    }
+
     #[test]
     fn test_format_size() {
         assert_eq!(format_size(500), "500 B");
@@ -56,6 +365,12 @@ mod tests {
         assert_eq!(format_size(gib), "1.0 GiB");
     }
 
+    #[test]
+    fn test_display_size() {
+        assert_eq!(display_size(1536, false), "1.5 KiB");
+        assert_eq!(display_size(1536, true), "1536");
+    }
+
     #[test]
     #[cfg(unix)]
     fn test_format_permissions() {
@@ -68,5 +383,50 @@ mod tests {
         // -rwx------
         let mode_user_only = 0o700;
         assert_eq!(format_permissions(mode_user_only), "rwx------");
+        // Special bits, with and without the execute bit under them
+        assert_eq!(format_permissions(0o4755), "rwsr-xr-x");
+        assert_eq!(format_permissions(0o2644), "rw-r-Sr--");
+        assert_eq!(format_permissions(0o1777), "rwxrwxrwt");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_display_os_str() {
+        use std::os::unix::ffi::OsStrExt;
+        assert_eq!(display_os_str(OsStr::new("plain.txt")), "plain.txt");
+        let invalid = OsStr::from_bytes(b"caf\xe9-\xff\xfe.txt");
+        assert_eq!(display_os_str(invalid), "caf\\xe9-\\xff\\xfe.txt");
+        // A valid multi-byte character next to an invalid byte stays intact.
+        let mixed = OsStr::from_bytes(b"\xc3\xa9\x80");
+        assert_eq!(display_os_str(mixed), "\u{e9}\\x80");
+    }
+
+    #[test]
+    fn test_glob_set() {
+        let set = glob_set(&["*/src".to_string(), "v?.*".to_string()]).unwrap();
+        assert!(set.is_match("app/src"));
+        assert!(!set.is_match("crates/core/src"));
+        assert!(!set.is_match("src"));
+        assert!(!set.is_match("app/src/bin"));
+        assert!(set.is_match("v1.2"));
+        let deep = glob_set(&["**/src".to_string()]).unwrap();
+        assert!(deep.is_match("crates/core/src"));
+        assert!(deep.is_match("src"));
+        assert!(glob_set(&["a[b".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        use std::time::Duration;
+        let now = SystemTime::now();
+        let ago = |secs| now - Duration::from_secs(secs);
+        assert_eq!(format_relative_time(ago(5), now), "5s");
+        assert_eq!(format_relative_time(ago(120), now), "2m");
+        assert_eq!(format_relative_time(ago(3 * 3600), now), "3h");
+        assert_eq!(format_relative_time(ago(2 * 86400), now), "2d");
+        assert_eq!(format_relative_time(ago(14 * 86400), now), "2w");
+        assert_eq!(format_relative_time(ago(90 * 86400), now), "3mo");
+        assert_eq!(format_relative_time(ago(800 * 86400), now), "2y");
+        assert_eq!(format_relative_time(now + Duration::from_secs(60), now), "now");
     }
 }
@@ -1,23 +1,50 @@
 //! Implements the classic, non-interactive directory tree view.
 
-use crate::app::ViewArgs;
+use crate::app::{
+    ChecksumAlgorithm, GitStatusPosition, GitSubmodules, OutputFormat, ShowErrors, ViewArgs,
+};
+use crate::checksum;
 use crate::git;
 use crate::icons;
+use crate::json;
+use crate::lines;
+use crate::profile::Profile;
+use crate::report;
 use crate::sort;
+use crate::template::Field;
+use crate::tui;
 use crate::utils;
-use colored::{control, Colorize};
-use ignore::{self, WalkBuilder};
+use crate::watch;
+use colored::{control, ColoredString, Colorize};
+use globset::GlobSet;
+use ignore::{self, DirEntry, WalkBuilder};
 use lscolors::LsColors;
+use ratatui::crossterm::terminal;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use unicode_width::UnicodeWidthChar;
 use url::Url;
 
 // Platform-specific import for unix permissions
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
+/// Background colors for --highlight, cycled through in the order the texts are given.
+const HIGHLIGHT_COLORS: [colored::Color; 4] = [
+    colored::Color::BrightYellow,
+    colored::Color::BrightCyan,
+    colored::Color::BrightMagenta,
+    colored::Color::BrightGreen,
+];
+
 /// Executes the classic directory tree view
-pub fn run(args: &ViewArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
+///
+/// Returns whether any entries were listed, which decides the exit status with --exit-code.
+pub fn run(args: &ViewArgs, ls_colors: &LsColors) -> anyhow::Result<bool> {
     if !args.path.is_dir() {
         anyhow::bail!("'{}' is not a directory.", args.path.display());
     }
@@ -30,8 +57,130 @@ pub fn run(args: &ViewArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
         crate::app::ColorChoice::Auto => {}
     }
 
+    let fitted_args;
+    let args = match terminal_height().filter(|_| args.fit) {
+        Some(height) => {
+            fitted_args = ViewArgs { level: Some(fit_level(args, height)), ..args.clone() };
+            &fitted_args
+        }
+        None => args,
+    };
+
+    if args.watch {
+        let debounce = Duration::from_millis(args.debounce);
+        let watcher = watch::FsWatcher::new(&canonical_root, debounce)?;
+        loop {
+            let listed = redraw_tree(args, ls_colors, &canonical_root)?;
+            if !watcher.wait_for_change() {
+                return Ok(listed);
+            }
+        }
+    }
+
+    // Runs until interrupted with Ctrl-C.
+    if let Some(seconds) = args.repeat {
+        loop {
+            redraw_tree(args, ls_colors, &canonical_root)?;
+            thread::sleep(Duration::from_secs(seconds));
+        }
+    }
+
+    let mut profile = Profile::new(args.profile);
+    let listed = render_tree(args, ls_colors, &canonical_root, &mut profile)?;
+    profile.report("render");
+    if !listed && args.interactive_on_empty {
+        tui::run(&args.to_interactive_args(), ls_colors)?;
+    }
+    Ok(listed)
+}
+
+/// Styles an entry's permissions for -p, flagging security-relevant modes: setuid or
+/// setgid in bold yellow, and world-writable in red unless the sticky bit is set, as on
+/// shared directories like `/tmp`. Sticky entries are left undimmed; the rest are dimmed.
+#[cfg(unix)]
+fn style_permissions(perms: String, mode: u32) -> ColoredString {
+    if mode & 0o6000 != 0 {
+        perms.yellow().bold()
+    } else if mode & 0o1000 != 0 {
+        perms.normal()
+    } else if mode & 0o002 != 0 {
+        perms.red()
+    } else {
+        perms.dimmed()
+    }
+}
+
+/// Returns the terminal's height in lines, or `None` when stdout is not a terminal.
+fn terminal_height() -> Option<usize> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    terminal::size().ok().map(|(_, rows)| usize::from(rows))
+}
+
+/// Finds the deepest level, up to --level, whose tree fits in `height` lines for --fit.
+///
+/// Each level is counted with its own walk, which stops as soon as the entries overflow,
+/// so a huge tree costs no more than a screenful per level. Falls back to 1 when even
+/// the first level doesn't fit.
+fn fit_level(args: &ViewArgs, height: usize) -> usize {
+    // The root line, the blank line, and the summary take up three lines.
+    let available = height.saturating_sub(3);
+    let mut builder = WalkBuilder::new(&args.path);
+    builder.hidden(!args.all).git_ignore(args.gitignore).same_file_system(args.one_filesystem);
+    builder.add_custom_ignore_filename(utils::LSTR_IGNORE_FILENAME);
+
+    let mut fitting = 1;
+    let mut previous_count = None;
+    for depth in 1..=args.level.unwrap_or(usize::MAX) {
+        let count = builder
+            .max_depth(Some(depth))
+            .build()
+            .flatten()
+            .filter(|entry| entry.depth() > 0)
+            .filter(|entry| !args.dirs_only || entry.file_type().is_some_and(|ft| ft.is_dir()))
+            .take(available + 1)
+            .count();
+        // Stop once the tree overflows, or once going deeper adds nothing.
+        if count > available || previous_count == Some(count) {
+            break;
+        }
+        fitting = depth;
+        previous_count = Some(count);
+    }
+    fitting
+}
+
+/// Clears the screen and prints the tree again for --watch and --repeat.
+///
+/// The frame is sent as one synchronized update, so terminals that support it swap it in
+/// without flickering.
+fn redraw_tree(
+    args: &ViewArgs,
+    ls_colors: &LsColors,
+    canonical_root: &Path,
+) -> anyhow::Result<bool> {
+    let mut stdout = io::stdout();
+    _ = write!(stdout, "{}{}", watch::BEGIN_SYNCHRONIZED_UPDATE, watch::CLEAR_SCREEN);
+    let rendered = render_tree(args, ls_colors, canonical_root, &mut Profile::new(false));
+    _ = write!(stdout, "{}", watch::END_SYNCHRONIZED_UPDATE);
+    _ = stdout.flush();
+    rendered
+}
+
+/// Walks the tree once and prints it, followed by the summary line.
+///
+/// Returns whether any entries were listed. With --interactive-on-empty, nothing at all
+/// is printed when there are none. The phases up to sorting are timed into `profile`,
+/// leaving the rendering as its current phase.
+fn render_tree(
+    args: &ViewArgs,
+    ls_colors: &LsColors,
+    canonical_root: &Path,
+    profile: &mut Profile,
+) -> anyhow::Result<bool> {
     // Format root directory with same alignment as tree entries
-    let root_metadata = if args.size || args.permissions { 
+    let root_metadata = if args.size || args.permissions || args.time { 
         fs::metadata(&args.path).ok() 
     } else { 
         None 
@@ -42,7 +191,7 @@ pub fn run(args: &ViewArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
             #[cfg(unix)]
             {
                 let mode = md.permissions().mode();
-                let file_type_char = if md.is_dir() { 'd' } else { '-' };
+                let file_type_char = utils::FileKind::of(md.file_type()).type_char();
                 format!("{}{}", file_type_char, utils::format_permissions(mode))
             }
             #[cfg(not(unix))]
@@ -58,34 +207,88 @@ pub fn run(args: &ViewArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
         String::new()
     };
     
-    let root_git_status_str = if args.git_status {
-        "  ".to_string() // Empty git status column for consistent spacing
-    } else {
-        String::new()
-    };
+    let root_git_status_str =
+        if args.shows_git_status() && args.git_status_position == GitStatusPosition::Left {
+            "  ".to_string() // Empty git status column for consistent spacing
+        } else {
+            String::new()
+        };
     
-    if writeln!(
-        io::stdout(), 
-        "{}{}{}",
-        root_git_status_str,
-        root_permissions_str, 
-        args.path.display().to_string().blue().bold()
-    ).is_err() {
-        return Ok(());
-    }
+    // Paths are displayed relative to this base when --relative-to is given.
+    let relative_base = match &args.relative_to {
+        Some(base) => match fs::canonicalize(base) {
+            Ok(base) => Some(base),
+            Err(_) => anyhow::bail!("'{}' is not a valid --relative-to base.", base.display()),
+        },
+        None => None,
+    };
+    // A root given as a symlink shows both ends, e.g. `link -> /real/target`. Components
+    // drop a trailing slash, which would otherwise make the link resolve.
+    let root_is_link = fs::symlink_metadata(args.path.components().as_path())
+        .is_ok_and(|md| md.file_type().is_symlink());
+    let root_display = match (&args.root_label, &relative_base) {
+        (Some(label), _) => label.clone(),
+        (None, Some(base)) => format_display_path(canonical_root, base),
+        (None, None) if args.canonical => canonical_root.display().to_string(),
+        (None, None) if (args.real_path || root_is_link) && args.path != canonical_root => {
+            format!("{} -> {}", args.path.display(), canonical_root.display())
+        }
+        (None, None) => canonical_root.display().to_string(),
+    };
+
+    profile.phase("setup");
+    // Loaded before the header so an invalid --since-commit revision fails cleanly.
+    let git_repo_status = match &args.since_commit {
+        Some(rev) => Some(git::load_changes_since(canonical_root, rev)?),
+        None if args.shows_git_status() => {
+            git::load_status(canonical_root, args.git_submodules == GitSubmodules::Show)?
+        }
+        None => None,
+    };
+    profile.phase("git status");
+
+    // Every entry's age is measured against the same moment.
+    let now = SystemTime::now();
+    let format_time = |metadata: Option<&fs::Metadata>| {
+        let modified = metadata.and_then(|md| md.modified().ok());
+        let age = modified.map(|time| utils::format_relative_time(time, now));
+        format!("{:>4} ", age.unwrap_or_default())
+    };
+    let root_time_str = if args.time { format_time(root_metadata.as_ref()) } else { String::new() };
+
+    // The root has no checksum, so its column is left blank.
+    let root_checksum_str = args.checksum.map(|a| " ".repeat(a.hex_len() + 1)).unwrap_or_default();
 
-    let git_repo_status = if args.git_status { git::load_status(&canonical_root)? } else { None };
     let status_cache = git_repo_status.as_ref().map(|s| &s.cache);
+    let dirty_dirs = git_repo_status.as_ref().map(|s| &s.dirty_dirs);
     let repo_root = git_repo_status.as_ref().map(|s| &s.root);
 
     let mut builder = WalkBuilder::new(&args.path);
-    builder.hidden(!args.all).git_ignore(args.gitignore);
+    builder.hidden(!args.all).git_ignore(args.gitignore).same_file_system(args.one_filesystem);
+    builder.add_custom_ignore_filename(utils::LSTR_IGNORE_FILENAME);
+    let sort_options = args.to_sort_options();
     if let Some(level) = args.level {
-        builder.max_depth(Some(level));
+        // Child counts need the level below the deepest one shown.
+        builder.max_depth(Some(level + usize::from(sort_options.counts_children())));
+    }
+    if let Some(status) = &git_repo_status {
+        let opaque_dirs = status.opaque_dirs_under(&args.path, canonical_root);
+        if !opaque_dirs.is_empty() {
+            // Submodules are listed but not descended into.
+            builder.filter_entry(move |entry| {
+                !entry.path().parent().is_some_and(|parent| opaque_dirs.iter().any(|d| d == parent))
+            });
+        }
     }
 
     let mut dir_count = 0;
     let mut file_count = 0;
+    let mut total_size: u64 = 0;
+    // Entries per level for --depth-summary, starting with depth 1.
+    let mut depth_counts: Vec<usize> = Vec::new();
+
+    // Walk errors folded into the tree with --show-errors inline, keyed by path.
+    let mut walk_errors: HashMap<PathBuf, String> = HashMap::new();
 
     // Collect all entries first, then sort them
     let mut entries: Vec<_> = builder
@@ -99,98 +302,426 @@ pub fn run(args: &ViewArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
                 }
             }
             Err(err) => {
+                if args.show_errors == ShowErrors::Inline {
+                    if let (Some(path), message) = describe_walk_error(&err) {
+                        walk_errors.insert(path, message);
+                        return None;
+                    }
+                }
                 eprintln!("lstr: ERROR: {err}");
                 None
             }
         })
         .collect();
 
+    // With --since-commit or --git-only-changed, keep only changed files and the
+    // directories leading to them.
+    let only_changed = args.since_commit.is_some() || args.git_only_changed;
+    if let (true, Some(status)) = (only_changed, &git_repo_status) {
+        let changed = status.paths_with_ancestors(&args.path, canonical_root);
+        entries.retain(|entry| changed.contains(entry.path()));
+    }
+
+    if !args.prune_matching.is_empty() {
+        prune_matching(&mut entries, &utils::glob_set(&args.prune_matching)?);
+    }
+    let child_counts = if sort_options.counts_children() {
+        sort::count_children(&entries)
+    } else {
+        sort::ChildCounts::new()
+    };
+    if let Some(level) = args.level {
+        entries.retain(|entry| entry.depth() <= level);
+    }
+    profile.phase("walk");
+
+    // Read once, then shared by sorting, the size limits, and the columns.
+    let metadata_cache = if args.reads_metadata() {
+        utils::read_metadata(&entries, args.dereference)
+    } else {
+        utils::MetadataCache::new()
+    };
+    profile.phase("metadata");
+
     // Apply tree-aware sorting (preserves parent-child relationships)
-    let sort_options = args.to_sort_options();
-    sort::sort_entries_hierarchically(&mut entries, &sort_options);
+    sort::sort_entries_hierarchically(&mut entries, &sort_options, &metadata_cache, &child_counts);
+    profile.phase("sort");
 
-    // Build tree structure information
-    let tree_info = build_tree_info(&entries);
+    if args.count_by_extension {
+        return Ok(report::print_extension_counts(&entries, args).unwrap_or(true));
+    }
+    if args.group_by_extension {
+        return Ok(report::print_extension_groups(&entries, args).unwrap_or(true));
+    }
 
-    for (index, entry) in entries.iter().enumerate() {
+    // With --mark-empty, directories with a walked child are known not to be empty, so
+    // only the rest are checked on disk. Collected before any entries are dropped below.
+    let non_empty_dirs: HashSet<PathBuf> = if args.mark_empty {
+        entries.iter().filter_map(|entry| entry.path().parent()).map(Path::to_path_buf).collect()
+    } else {
+        HashSet::new()
+    };
+
+    // Also before --dirs-only, since a directory can collide with a file.
+    let case_collisions =
+        if args.warn_case_collisions { find_case_collisions(&entries) } else { HashSet::new() };
+
+    // Counted before --dirs-only drops the files themselves.
+    let dir_summaries = match args.collapse_under {
+        Some(threshold) => summarize_small_dirs(&entries, threshold, &metadata_cache),
+        None => HashMap::new(),
+    };
+    if !dir_summaries.is_empty() {
+        entries.retain(|entry| {
+            !entry.path().ancestors().skip(1).any(|dir| dir_summaries.contains_key(dir))
+        });
+    }
+
+    if args.dirs_only {
+        entries.retain(|entry| entry.file_type().is_some_and(|ft| ft.is_dir()));
+    }
+    if args.group_by_initial {
+        return Ok(report::print_initial_groups(&entries, args).unwrap_or(true));
+    }
+
+    // With --max-total-size, the tree ends with the file that takes the running total of
+    // file sizes past the limit, in display order. That order is only known once the whole
+    // tree is sorted, so the walk can't stop any earlier, but the files past the cut are
+    // never stat-ed.
+    let mut omitted_count = 0;
+    if let Some(limit) = args.max_total_size {
+        let mut shown_bytes: u64 = 0;
+        let cut = entries.iter().position(|entry| {
+            let exceeded = shown_bytes > limit;
+            if !exceeded && !entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                let size = match metadata_cache.get(entry.path()) {
+                    Some(md) => Some(md.len()),
+                    None => utils::entry_metadata(entry, args.dereference).map(|md| md.len()),
+                };
+                shown_bytes += size.unwrap_or(0);
+            }
+            exceeded
+        });
+        if let Some(cut) = cut {
+            omitted_count = entries.len() - cut;
+            entries.truncate(cut);
+        }
+    }
+
+    let level_omitted_count = match args.entry_limit_per_level {
+        Some(limit) => limit_per_level(&mut entries, limit),
+        None => 0,
+    };
+
+    if args.output == OutputFormat::TreeJson {
+        return Ok(json::print_tree_json(&entries, args, &walk_errors).unwrap_or(true));
+    }
+
+    // First pass for --size-color: the largest file sets the top of the scale.
+    let max_file_size = if args.size_color {
+        entries
+            .iter()
+            .filter(|entry| !entry.file_type().is_some_and(|ft| ft.is_dir()))
+            .filter_map(|entry| metadata_cache.get(entry.path()))
+            .map(|md| md.len())
+            .max()
+    } else {
+        None
+    };
+
+    // Lay out the rows and build tree structure information
+    let rows = limit_children(&entries, &args.path, args.max_files_per_dir);
+    let collapsed =
+        if args.collapse_single_child { find_single_child_dirs(&rows) } else { HashSet::new() };
+    let tree_info = build_tree_info(&rows, &collapsed);
+    let default_tree_info = (String::new(), "└──".to_string());
+
+    // Nothing is printed yet, so the interactive viewer can take over cleanly.
+    if rows.is_empty() && args.interactive_on_empty {
+        return Ok(false);
+    }
+
+    let files: Vec<&Path> = rows
+        .iter()
+        .filter_map(|row| match row {
+            TreeRow::Entry(entry) if entry.file_type().is_some_and(|ft| ft.is_file()) => {
+                Some(entry.path())
+            }
+            _ => None,
+        })
+        .collect();
+
+    // Lines are counted before the header, since the column is as wide as the largest count.
+    let line_counts = if args.loc { lines::count_lines_in_files(&files) } else { HashMap::new() };
+    let loc_width = line_counts.values().max().map_or(1, |max| max.to_string().len());
+    let root_loc_str = if args.loc { " ".repeat(loc_width + 1) } else { String::new() };
+
+    let header = format!(
+        "{}{}{}{}{}{}",
+        root_git_status_str,
+        root_permissions_str,
+        root_checksum_str,
+        root_time_str.dimmed(),
+        root_loc_str,
+        root_display.blue().bold()
+    );
+    if args.prints_tree()
+        && writeln!(io::stdout(), "{}", clip_line(header, args.max_width)).is_err()
+    {
+        return Ok(true);
+    }
+
+    if rows.is_empty()
+        && args.prints_tree()
+        && writeln!(io::stdout(), "{}", "(no matching entries)".dimmed()).is_err()
+    {
+        return Ok(true);
+    }
+
+    if args.output == OutputFormat::Tsv && !args.no_header {
+        let names: Vec<&str> = args.fields.iter().map(|field| field.name()).collect();
+        if writeln!(io::stdout(), "{}", names.join("\t")).is_err() {
+            return Ok(true);
+        }
+    }
+
+    // Hash every displayed file up front so the work can be spread across threads.
+    let checksum_algorithm = match args.checksum {
+        Some(algorithm) => Some(algorithm),
+        None if args.format_uses(Field::Checksum) => Some(ChecksumAlgorithm::default()),
+        None => None,
+    };
+    let checksums = match checksum_algorithm {
+        Some(algorithm) => checksum::checksum_files(&files, algorithm),
+        None => HashMap::new(),
+    };
+
+    // Marker rows leave the git status, permissions, checksum, and line count columns blank.
+    let mut column_padding = String::new();
+    if args.shows_git_status() && args.git_status_position == GitStatusPosition::Left {
+        column_padding.push_str("  ");
+    }
+    if args.permissions {
+        column_padding.push_str(&" ".repeat(11));
+    }
+    column_padding.push_str(&root_checksum_str);
+    if args.time {
+        column_padding.push_str(&" ".repeat(5));
+    }
+    column_padding.push_str(&root_loc_str);
+
+    // Sibling files collected onto a single line with --compact-files.
+    let mut file_group: Option<FileGroup> = None;
+    let line_width = compact_line_width().min(args.max_width.unwrap_or(usize::MAX));
+    // The depth of the previous row, to spot the end of a top-level subtree.
+    let mut previous_depth = 0;
+
+    for (index, row) in rows.iter().enumerate() {
+        let (prefix, connector) = tree_info.get(&index).unwrap_or(&default_tree_info);
+        let compact_file = args.compact_files
+            && matches!(row, TreeRow::Entry(e) if !e.file_type().is_some_and(|ft| ft.is_dir()));
+        if file_group.as_ref().is_some_and(|group| !compact_file || group.parent != row.parent()) {
+            if let Some(group) = file_group.take() {
+                if group.print(&column_padding, line_width, args.max_width).is_err() {
+                    break;
+                }
+            }
+        }
+        if args.section_spacing
+            && args.prints_tree()
+            && row.depth() == 1
+            && previous_depth > 1
+            && writeln!(io::stdout()).is_err()
+        {
+            break;
+        }
+        previous_depth = row.depth();
+        let entry = match row {
+            TreeRow::Entry(entry) => *entry,
+            TreeRow::More { count, .. } => {
+                let line = format!(
+                    "{column_padding}{prefix}{connector} {}",
+                    format!("... ({count} more)").dimmed()
+                );
+                if args.prints_tree()
+                    && writeln!(io::stdout(), "{}", clip_line(line, args.max_width)).is_err()
+                {
+                    break;
+                }
+                continue;
+            }
+        };
         let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
-        if args.dirs_only && !is_dir {
+        if args.depth_summary {
+            // The root is skipped, so depths start at 1.
+            let depth = entry.depth();
+            if depth_counts.len() < depth {
+                depth_counts.resize(depth, 0);
+            }
+            depth_counts[depth - 1] += 1;
+        }
+        if collapsed.contains(entry.path()) {
+            // Shown as part of its only child's name instead.
+            dir_count += 1;
             continue;
         }
 
-        let git_status_str = if let (Some(cache), Some(root)) = (status_cache, repo_root) {
-            if let Ok(canonical_entry) = entry.path().canonicalize() {
-                if let Ok(relative_path) = canonical_entry.strip_prefix(root) {
-                    cache
-                        .get(relative_path)
-                        .map(|s| {
-                            let status_char = s.get_char();
-                            let color = match s {
-                                git::FileStatus::New | git::FileStatus::Renamed => {
-                                    colored::Color::Green
-                                }
-                                git::FileStatus::Modified | git::FileStatus::Typechange => {
-                                    colored::Color::Yellow
-                                }
-                                git::FileStatus::Deleted => colored::Color::Red,
-                                git::FileStatus::Conflicted => colored::Color::BrightRed,
-                                git::FileStatus::Untracked => colored::Color::Magenta,
-                            };
-                            format!("{status_char} ").color(color).to_string()
-                        })
-                        .unwrap_or_else(|| "  ".to_string())
-                } else {
-                    "  ".to_string()
-                }
-            } else {
-                "  ".to_string()
+        // The entry's path relative to the repository root, which keys the status cache.
+        let git_path = repo_root.and_then(|root| {
+            let canonical_entry = entry.path().canonicalize().ok()?;
+            canonical_entry.strip_prefix(root).ok().map(Path::to_path_buf)
+        });
+        let git_entry = git_path.as_deref().and_then(|path| status_cache?.get(path));
+        let git_status_str = match git_entry {
+            Some(git_entry) => {
+                let status_char = git_entry.status.get_char();
+                let color = match git_entry.status {
+                    git::FileStatus::New | git::FileStatus::Renamed => colored::Color::Green,
+                    git::FileStatus::Modified | git::FileStatus::Typechange => {
+                        colored::Color::Yellow
+                    }
+                    git::FileStatus::Deleted => colored::Color::Red,
+                    git::FileStatus::Conflicted => colored::Color::BrightRed,
+                    git::FileStatus::Untracked => colored::Color::Magenta,
+                    git::FileStatus::Submodule => colored::Color::Cyan,
+                };
+                format!("{status_char} ").color(color).to_string()
             }
-        } else {
-            String::new()
+            // A directory without a status of its own may still hide changes.
+            None if is_dir
+                && git_path.as_ref().zip(dirty_dirs).is_some_and(|(p, d)| d.contains(p)) =>
+            {
+                "* ".yellow().to_string()
+            }
+            None if status_cache.is_some() => "  ".to_string(),
+            None => String::new(),
+        };
+        // Renamed files show where they came from, e.g. `new.rs (was old.rs)`.
+        let renamed_str = match (git_entry.and_then(|e| e.renamed_from.as_deref()), &git_path) {
+            (Some(old), Some(new)) => {
+                // A rename within one directory only needs the old name.
+                let old = match old.file_name() {
+                    Some(name) if old.parent() == new.parent() => Path::new(name),
+                    _ => old,
+                };
+                let old = utils::display_os_str(old.as_os_str());
+                format!(" (was {old})").dimmed().to_string()
+            }
+            _ => String::new(),
         };
 
-        let metadata = if args.size || args.permissions { entry.metadata().ok() } else { None };
-        let permissions_str = if args.permissions {
+        let metadata = metadata_cache.get(entry.path()).cloned();
+
+        let mut error_str = walk_errors
+            .remove(entry.path())
+            .map(|message| format!(" [{message}]").red().to_string())
+            .unwrap_or_default();
+        if case_collisions.contains(entry.path()) {
+            error_str.push_str(&" (case collision)".red().to_string());
+        }
+
+        if is_dir {
+            dir_count += 1;
+        } else {
+            file_count += 1;
+            total_size += metadata.as_ref().map_or(0, |m| m.len());
+        }
+        if args.summary_only {
+            continue;
+        }
+
+        let permissions_str = if args.permissions || args.format_uses(Field::Perms) {
             let perms = if let Some(md) = &metadata {
                 // <-- Use 'md' here
                 #[cfg(unix)]
                 {
                     // Use 'md' for Unix-specific logic
                     let mode = md.permissions().mode();
-                    let file_type_char = if md.is_dir() { 'd' } else { '-' };
-                    format!("{}{}", file_type_char, utils::format_permissions(mode))
+                    let file_type_char = utils::FileKind::of(md.file_type()).type_char();
+                    let perms = format!("{}{}", file_type_char, utils::format_permissions(mode));
+                    style_permissions(perms, mode)
                 }
                 #[cfg(not(unix))]
                 {
                     // This line tells the compiler we've intentionally not used 'md' on non-Unix systems
                     let _ = md;
-                    "----------".to_string()
+                    "----------".dimmed()
                 }
             } else {
-                "----------".to_string()
+                "----------".dimmed()
             };
             format!("{perms} ")
         } else {
             String::new()
         };
 
-        let default_tree_info = (String::new(), "└──".to_string());
-        let (prefix, connector) = tree_info.get(&index).unwrap_or(&default_tree_info);
-        let name = entry.file_name().to_string_lossy();
-        let icon_str = if args.icons {
-            let (icon, color) = icons::get_icon_for_path(entry.path(), is_dir);
-            format!("{} ", icon.color(color))
+        let digest = checksums.get(entry.path()).map_or("", String::as_str);
+        let checksum_str = match args.checksum {
+            Some(algorithm) => format!("{digest:<width$} ", width = algorithm.hex_len()),
+            None => String::new(),
+        };
+        let time_str = if args.time { format_time(metadata.as_ref()) } else { String::new() };
+        // Directories and binary files have no count and leave the column blank.
+        let loc_str = match line_counts.get(entry.path()) {
+            Some(count) => format!("{count:>loc_width$} "),
+            None if args.loc => " ".repeat(loc_width + 1),
+            None => String::new(),
+        };
+
+        let name = if args.full_path {
+            let path = match &relative_base {
+                Some(base) => {
+                    let relative = entry.path().strip_prefix(&args.path).unwrap_or(entry.path());
+                    format_display_path(&canonical_root.join(relative), base)
+                }
+                None if args.canonical => {
+                    let relative = entry.path().strip_prefix(&args.path).unwrap_or(entry.path());
+                    utils::display_os_str(canonical_root.join(relative).as_os_str()).into_owned()
+                }
+                None => utils::display_os_str(entry.path().as_os_str()).into_owned(),
+            };
+            match args.path_shorten {
+                Some(max_width) => shorten_path(&path, max_width),
+                None => path,
+            }
+        } else if collapsed.contains(row.parent()) {
+            collapsed_name(entry.path(), &collapsed)
+        } else {
+            utils::display_os_str(entry.file_name()).into_owned()
+        };
+        let icon_str = if args.icons.is_some_and(|mode| mode.shows(is_dir)) {
+            if !is_dir && icons::is_excluded(entry.path(), &args.no_icons_for) {
+                // Blank, so the names still line up with their siblings'.
+                "  ".to_string()
+            } else {
+                let (icon, color) = icons::get_icon_for_path(entry.path(), is_dir);
+                format!("{} ", icon.color(color))
+            }
         } else {
             String::new()
         };
-        let size_str = if args.size && !is_dir {
+        let size_str = if let Some(summary) = dir_summaries.get(entry.path()) {
+            let bytes = utils::display_size(summary.bytes, args.bytes);
+            format!(" ({} files, {bytes})", summary.files)
+        } else if args.size && (!is_dir || args.show_size_always) {
             metadata
                 .as_ref()
-                .map(|m| format!(" ({})", utils::format_size(m.len())))
+                .map(|m| format!(" ({})", utils::display_size(m.len(), args.bytes)))
                 .unwrap_or_default()
         } else {
             String::new()
         };
+        let is_empty = args.mark_empty
+            && if is_dir {
+                !non_empty_dirs.contains(entry.path())
+                    && fs::read_dir(entry.path())
+                        .is_ok_and(|mut children| children.next().is_none())
+            } else {
+                metadata.as_ref().is_some_and(|md| md.len() == 0)
+            };
+        let size_str = if is_empty { size_str + " (empty)" } else { size_str };
+
+        let kind = entry.file_type().map_or(utils::FileKind::File, utils::FileKind::of);
 
         // --- Corrected Logic Block ---
         let ls_style = ls_colors.style_for_path(entry.path()).cloned().unwrap_or_default();
@@ -219,6 +750,8 @@ pub fn run(args: &ViewArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
                 LsColor::RGB(r, g, b) => colored::Color::TrueColor { r, g, b },
             };
             styled_name = styled_name.color(color);
+        } else if let Some(color) = kind.default_color() {
+            styled_name = styled_name.color(color);
         }
 
         if ls_style.font_style.bold {
@@ -230,6 +763,18 @@ pub fn run(args: &ViewArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
         if ls_style.font_style.underline {
             styled_name = styled_name.underline();
         }
+        if let (Some(max), Some(md)) = (max_file_size, &metadata) {
+            if !is_dir {
+                styled_name = apply_size_scale(styled_name, md.len(), max);
+            }
+        }
+        if let Some(index) = args.highlight.iter().position(|text| name.contains(text.as_str())) {
+            let color = HIGHLIGHT_COLORS[index % HIGHLIGHT_COLORS.len()];
+            styled_name = styled_name.black().on_color(color);
+        }
+        if args.dim_beyond.is_some_and(|limit| entry.depth() > limit) {
+            styled_name = styled_name.dimmed();
+        }
 
         let final_name = if args.hyperlinks && !is_dir {
             // Canonicalize the path to get an absolute path for the URL
@@ -245,71 +790,724 @@ pub fn run(args: &ViewArgs, ls_colors: &LsColors) -> anyhow::Result<()> {
         } else {
             styled_name.to_string()
         };
-
-        if is_dir {
-            dir_count += 1;
+        // As with `ls -F`, the indicator follows the name uncolored.
+        let final_name = if args.classify {
+            let executable = metadata.as_ref().is_some_and(utils::is_executable);
+            format!("{final_name}{}", kind.classify_suffix(executable))
         } else {
-            file_count += 1;
+            final_name
+        };
+        let final_name = match args.canonical.then(|| link_target(entry)).flatten() {
+            Some(target) => format!("{final_name} -> {target}"),
+            None => final_name,
+        };
+
+        // The git status marker either leads the line or sits right before the name.
+        let (left_status_str, name_status_str) = match args.git_status_position {
+            GitStatusPosition::Left => (git_status_str.as_str(), ""),
+            GitStatusPosition::Name => ("", git_status_str.as_str()),
+        };
+
+        let field_text = |field| match field {
+            Field::Tree => format!("{prefix}{connector} "),
+            Field::Name => final_name.clone(),
+            Field::Path => utils::display_os_str(entry.path().as_os_str()).into_owned(),
+            Field::Icon => icon_str.trim_end().to_string(),
+            Field::Perms => permissions_str.trim_end().to_string(),
+            Field::Checksum => digest.to_string(),
+            Field::Time => format_time(metadata.as_ref()).trim().to_string(),
+            Field::Size => match &metadata {
+                Some(md) if !is_dir || args.show_size_always => {
+                    utils::display_size(md.len(), args.bytes)
+                }
+                _ => String::new(),
+            },
+            Field::Git => git_status_str.trim_end().to_string(),
+            Field::Error => error_str.trim_start().to_string(),
+        };
+
+        // Flat output for other programs, so it is never clipped.
+        if !args.fields.is_empty() {
+            let values = args.fields.iter().map(|&field| field_text(field));
+            let line = match args.output {
+                // Tree JSON returned before the rows were laid out.
+                OutputFormat::Lines | OutputFormat::TreeJson => {
+                    values.collect::<Vec<_>>().join(&args.separator)
+                }
+                OutputFormat::Tsv => {
+                    let values: Vec<String> = values
+                        .map(|value| tsv_value(value, args.strict))
+                        .collect::<anyhow::Result<_>>()?;
+                    values.join("\t")
+                }
+            };
+            if writeln!(io::stdout(), "{line}").is_err() {
+                break;
+            }
+            continue;
         }
 
-        if writeln!(
-            io::stdout(),
-            "{}{}{}{} {}{}{}",
-            git_status_str,
-            permissions_str.dimmed(),
+        if let Some(template) = &args.format {
+            let line = template.render(|field| {
+                let text = field_text(field);
+                let width = visible_width(&text);
+                (text, width)
+            });
+            if writeln!(io::stdout(), "{}", clip_line(line, args.max_width)).is_err() {
+                break;
+            }
+            continue;
+        }
+
+        if compact_file {
+            let group = file_group.get_or_insert_with(|| FileGroup {
+                parent: row.parent().to_path_buf(),
+                prefix: prefix.clone(),
+                connector: String::new(),
+                items: Vec::new(),
+            });
+            // The group ends where its last file is, so that file decides the connector.
+            group.connector.clone_from(connector);
+            let item = format!(
+                "{name_status_str}{icon_str}{final_name}{renamed_str}{}{error_str}",
+                size_str.dimmed()
+            );
+            group.items.push((visible_width(&item), item));
+            continue;
+        }
+
+        let line = format!(
+            "{}{}{}{}{}{}{} {}{}{}{}{}{}",
+            left_status_str,
+            permissions_str,
+            checksum_str.dimmed(),
+            time_str.dimmed(),
+            loc_str.dimmed(),
             prefix,
             connector,
+            name_status_str,
             icon_str,
             final_name,
-            size_str.dimmed()
-        )
-        .is_err()
-        {
+            renamed_str,
+            size_str.dimmed(),
+            error_str
+        );
+        if writeln!(io::stdout(), "{}", clip_line(line, args.max_width)).is_err() {
             break;
         }
     }
 
-    let summary = format!("\n{dir_count} directories, {file_count} files");
-    _ = writeln!(io::stdout(), "{summary}");
+    if let Some(group) = file_group {
+        _ = group.print(&column_padding, line_width, args.max_width);
+    }
+
+    if let (Some(limit), true) = (args.max_total_size, omitted_count > 0 && args.prints_tree()) {
+        let notice = format!(
+            "... ({omitted_count} more, stopped past {} of files)",
+            utils::format_size(limit)
+        );
+        _ = writeln!(io::stdout(), "{}", notice.dimmed());
+    }
+    if let (Some(limit), true) =
+        (args.entry_limit_per_level, level_omitted_count > 0 && args.prints_tree())
+    {
+        let notice = format!("... ({level_omitted_count} more past {limit} entries per level)");
+        _ = writeln!(io::stdout(), "{}", notice.dimmed());
+    }
+
+    // Errors that don't belong to a displayed entry still go to stderr.
+    for (path, message) in &walk_errors {
+        eprintln!("lstr: ERROR: {}: {message}", path.display());
+    }
+
+    if !args.fields.is_empty() {
+        return Ok(!rows.is_empty());
+    }
 
-    Ok(())
+    let mut summary = format!("{dir_count} directories, {file_count} files");
+    // Under the tree, the total is only added when --bytes or --total-human asks for it.
+    if args.size && (args.summary_only || args.bytes || args.total_human) {
+        // --total-human keeps the total readable even when lines show raw bytes.
+        let raw_total = args.bytes && !args.total_human;
+        summary.push_str(&format!(", {} total", utils::display_size(total_size, raw_total)));
+    }
+    if args.loc {
+        summary.push_str(&format!(", {} lines", line_counts.values().sum::<u64>()));
+    }
+    if let (true, Some(level)) = (args.count_hidden, args.level) {
+        // Walked again without the depth cap, keeping the other filters.
+        let hidden = builder
+            .max_depth(None)
+            .build()
+            .flatten()
+            .filter(|entry| entry.depth() > level)
+            .filter(|entry| !args.dirs_only || entry.file_type().is_some_and(|ft| ft.is_dir()))
+            .count();
+        summary.push_str(&format!(" ({hidden} more below depth limit)"));
+    }
+    // The blank line separates the summary from the tree, which summary-only mode omits.
+    let separator = if args.summary_only || args.no_summary_newline { "" } else { "\n" };
+    _ = writeln!(io::stdout(), "{separator}{}", clip_line(summary, args.max_width));
+    if !depth_counts.is_empty() {
+        let levels: Vec<String> = depth_counts
+            .iter()
+            .enumerate()
+            .map(|(index, count)| format!("depth {}: {count}", index + 1))
+            .collect();
+        _ = writeln!(io::stdout(), "{}", clip_line(levels.join(", "), args.max_width));
+    }
+
+    Ok(!rows.is_empty())
 }
 
+/// Keeps the first `limit` entries at each depth, in display order and across all
+/// directories, and drops the rest along with everything under them. Returns how many
+/// entries were dropped.
+fn limit_per_level(entries: &mut Vec<DirEntry>, limit: usize) -> usize {
+    let before = entries.len();
+    let mut shown_per_depth: Vec<usize> = Vec::new();
+    let mut dropped_dirs: HashSet<PathBuf> = HashSet::new();
+    entries.retain(|entry| {
+        if entry.path().ancestors().skip(1).any(|dir| dropped_dirs.contains(dir)) {
+            return false;
+        }
+        // The root is skipped, so depths start at 1.
+        let depth = entry.depth();
+        if shown_per_depth.len() < depth {
+            shown_per_depth.resize(depth, 0);
+        }
+        if shown_per_depth[depth - 1] < limit {
+            shown_per_depth[depth - 1] += 1;
+            return true;
+        }
+        if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+            dropped_dirs.insert(entry.path().to_path_buf());
+        }
+        false
+    });
+    before - entries.len()
+}
+
+/// Drops the entries whose name matches one of `patterns`, along with everything under
+/// the matching directories. Directories that held a dropped entry go too once nothing is
+/// left in them, while directories that were empty to begin with stay.
+fn prune_matching(entries: &mut Vec<DirEntry>, patterns: &GlobSet) {
+    let pruned: HashSet<PathBuf> = entries
+        .iter()
+        .filter(|entry| patterns.is_match(entry.file_name()))
+        .map(|entry| entry.path().to_path_buf())
+        .collect();
+    if pruned.is_empty() {
+        return;
+    }
+    entries.retain(|entry| !entry.path().ancestors().any(|path| pruned.contains(path)));
+
+    // A directory that lost entries stays only if a file, or a directory that lost
+    // nothing, is still below it.
+    let touched: HashSet<&Path> = pruned.iter().flat_map(|path| path.ancestors().skip(1)).collect();
+    let is_dir = |entry: &DirEntry| entry.file_type().is_some_and(|ft| ft.is_dir());
+    let kept: HashSet<PathBuf> = entries
+        .iter()
+        .filter(|&entry| !is_dir(entry) || !touched.contains(entry.path()))
+        .flat_map(|entry| entry.path().ancestors().skip(1))
+        .map(Path::to_path_buf)
+        .collect();
+    entries.retain(|entry| {
+        !is_dir(entry) || !touched.contains(entry.path()) || kept.contains(entry.path())
+    });
+}
+
+/// Makes a value fit in a TSV cell by escaping tabs, line breaks, and backslashes. With
+/// `strict`, values holding tabs or line breaks are an error instead.
+fn tsv_value(value: String, strict: bool) -> anyhow::Result<String> {
+    if strict {
+        if value.contains(['\t', '\n', '\r']) {
+            anyhow::bail!("{value:?} contains a tab or line break, which --strict doesn't allow.");
+        }
+        return Ok(value);
+    }
+    if !value.contains(['\t', '\n', '\r', '\\']) {
+        return Ok(value);
+    }
+    let mut escaped = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    Ok(escaped)
+}
+
+/// Colors a file name by its size relative to the largest file: tiny files are dimmed,
+/// then green, yellow, and red for the files holding the bulk of the data.
+fn apply_size_scale(name: ColoredString, size: u64, max_size: u64) -> ColoredString {
+    let ratio = if max_size == 0 { 0.0 } else { size as f64 / max_size as f64 };
+    if ratio < 0.01 {
+        name.dimmed()
+    } else if ratio < 0.1 {
+        name.green()
+    } else if ratio < 0.5 {
+        name.yellow()
+    } else {
+        name.red()
+    }
+}
+
+/// A line of the rendered tree: a walked entry or a marker for hidden children.
+enum TreeRow<'a> {
+    Entry(&'a DirEntry),
+    /// Stands in for `count` children of `parent` cut by --max-files-per-dir.
+    More {
+        parent: PathBuf,
+        depth: usize,
+        count: usize,
+    },
+}
+
+impl TreeRow<'_> {
+    fn depth(&self) -> usize {
+        match self {
+            TreeRow::Entry(entry) => entry.depth(),
+            TreeRow::More { depth, .. } => *depth,
+        }
+    }
+
+    fn parent(&self) -> &Path {
+        match self {
+            TreeRow::Entry(entry) => entry.path().parent().unwrap_or(Path::new("")),
+            TreeRow::More { parent, .. } => parent,
+        }
+    }
+}
+
+/// The contents of a directory listed in summary form by --collapse-under.
+#[derive(Default)]
+struct DirSummary {
+    files: usize,
+    bytes: u64,
+}
+
+/// Counts the files and bytes under every directory, keeping those that hold fewer than
+/// `threshold` files.
+fn summarize_small_dirs(
+    entries: &[DirEntry],
+    threshold: usize,
+    metadata: &utils::MetadataCache,
+) -> HashMap<PathBuf, DirSummary> {
+    let is_dir = |entry: &DirEntry| entry.file_type().is_some_and(|ft| ft.is_dir());
+    let mut summaries: HashMap<PathBuf, DirSummary> = entries
+        .iter()
+        .filter(|entry| is_dir(entry))
+        .map(|entry| (entry.path().to_path_buf(), DirSummary::default()))
+        .collect();
+    for entry in entries.iter().filter(|entry| !is_dir(entry)) {
+        let size = metadata.get(entry.path()).map_or(0, |md| md.len());
+        for dir in entry.path().ancestors().skip(1) {
+            // The walk root and everything above it are not tracked.
+            let Some(summary) = summaries.get_mut(dir) else {
+                break;
+            };
+            summary.files += 1;
+            summary.bytes += size;
+        }
+    }
+    summaries.retain(|_, summary| summary.files < threshold);
+    summaries
+}
+
+/// Lays out the sorted entries as tree rows, keeping at most `limit` children per
+/// directory. Cut children (and their subtrees) are replaced by a trailing marker row.
+fn limit_children<'a>(
+    entries: &'a [DirEntry],
+    root: &Path,
+    limit: Option<usize>,
+) -> Vec<TreeRow<'a>> {
+    let Some(limit) = limit else {
+        return entries.iter().map(TreeRow::Entry).collect();
+    };
+
+    let mut shown: HashMap<&Path, usize> = HashMap::new();
+    let mut hidden: HashMap<&Path, usize> = HashMap::new();
+    let mut hidden_dirs: Vec<&Path> = Vec::new();
+    let mut rows = Vec::new();
+    // Directories whose subtree is still being emitted, with their depth.
+    let mut open_dirs: Vec<(&Path, usize)> = Vec::new();
+
+    let more_row = |hidden: &HashMap<&Path, usize>, dir: &Path, depth: usize| {
+        hidden.get(dir).map(|&count| TreeRow::More { parent: dir.to_path_buf(), depth, count })
+    };
+
+    for entry in entries {
+        let path = entry.path();
+        if hidden_dirs.iter().any(|dir| path.starts_with(dir)) {
+            continue;
+        }
+        let parent = path.parent().unwrap_or(Path::new(""));
+        let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+        let count = shown.entry(parent).or_insert(0);
+        if *count >= limit {
+            *hidden.entry(parent).or_insert(0) += 1;
+            if is_dir {
+                hidden_dirs.push(path);
+            }
+            continue;
+        }
+        *count += 1;
+
+        // Close any directories whose subtree ends before this entry.
+        while let Some(&(dir, depth)) = open_dirs.last() {
+            if path.starts_with(dir) {
+                break;
+            }
+            rows.extend(more_row(&hidden, dir, depth + 1));
+            open_dirs.pop();
+        }
+        rows.push(TreeRow::Entry(entry));
+        if is_dir {
+            open_dirs.push((path, entry.depth()));
+        }
+    }
+    while let Some((dir, depth)) = open_dirs.pop() {
+        rows.extend(more_row(&hidden, dir, depth + 1));
+    }
+    rows.extend(more_row(&hidden, root, 1));
+    rows
+}
+
+/// Finds the directories whose only displayed child is another directory, which
+/// --collapse-single-child merges into that child's line.
+///
+/// The root is never collapsed, since it has no row of its own.
+fn find_single_child_dirs(rows: &[TreeRow]) -> HashSet<PathBuf> {
+    let mut children: HashMap<&Path, Vec<&TreeRow>> = HashMap::new();
+    for row in rows {
+        children.entry(row.parent()).or_default().push(row);
+    }
+    rows.iter()
+        .filter_map(|row| match row {
+            TreeRow::Entry(entry) => Some(entry.path()),
+            TreeRow::More { .. } => None,
+        })
+        .filter(|dir| match children.get(dir).map(Vec::as_slice) {
+            Some([TreeRow::Entry(child)]) => child.file_type().is_some_and(|ft| ft.is_dir()),
+            _ => false,
+        })
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// Returns the nearest ancestor of `dir` (or `dir` itself) that is not collapsed.
+fn visible_dir<'p>(dir: &'p Path, collapsed: &HashSet<PathBuf>) -> &'p Path {
+    let mut dir = dir;
+    while collapsed.contains(dir) {
+        match dir.parent() {
+            Some(parent) => dir = parent,
+            None => break,
+        }
+    }
+    dir
+}
+
+/// Returns the directory `row` is shown under, skipping collapsed directories.
+fn parent_of<'a>(row: &'a TreeRow, collapsed: &HashSet<PathBuf>) -> &'a Path {
+    visible_dir(row.parent(), collapsed)
+}
+
+/// Joins a path with its collapsed ancestors, e.g. `a/b/c` for `c`.
+fn collapsed_name(path: &Path, collapsed: &HashSet<PathBuf>) -> String {
+    let mut top = path;
+    while let Some(parent) = top.parent().filter(|parent| collapsed.contains(*parent)) {
+        top = parent;
+    }
+    let base = top.parent().unwrap_or(Path::new(""));
+    utils::display_os_str(path.strip_prefix(base).unwrap_or(path).as_os_str()).into_owned()
+}
+
+/// Files of one directory printed on a shared line by --compact-files.
+struct FileGroup {
+    parent: PathBuf,
+    prefix: String,
+    connector: String,
+    /// Each rendered file with its width in terminal columns.
+    items: Vec<(usize, String)>,
+}
+
+impl FileGroup {
+    /// Prints the collected files, wrapping onto continuation lines at `width` columns.
+    /// A file too wide to fit on a line by itself is clipped to `max_width`.
+    fn print(
+        &self,
+        column_padding: &str,
+        width: usize,
+        max_width: Option<usize>,
+    ) -> io::Result<()> {
+        let continuation = if self.connector == "├──" { "│  " } else { "   " };
+        let lead_width = visible_width(column_padding) + visible_width(&self.prefix) + 4;
+        let mut stdout = io::stdout();
+        let mut lead = self.connector.as_str();
+        let mut line = String::new();
+        let mut line_width = lead_width;
+
+        for (item_width, item) in &self.items {
+            if !line.is_empty() && line_width + 2 + item_width > width {
+                let full = format!("{column_padding}{}{lead} {line}", self.prefix);
+                writeln!(stdout, "{}", clip_line(full, max_width))?;
+                lead = continuation;
+                line.clear();
+                line_width = lead_width;
+            }
+            if !line.is_empty() {
+                line.push_str("  ");
+                line_width += 2;
+            }
+            line.push_str(item);
+            line_width += item_width;
+        }
+        let full = format!("{column_padding}{}{lead} {line}", self.prefix);
+        writeln!(stdout, "{}", clip_line(full, max_width))
+    }
+}
+
+/// Returns the column to wrap --compact-files lines at: the terminal width, or no limit
+/// when stdout is not a terminal.
+fn compact_line_width() -> usize {
+    if io::stdout().is_terminal() {
+        terminal::size().map_or(usize::MAX, |(columns, _)| usize::from(columns))
+    } else {
+        usize::MAX
+    }
+}
+
+/// Counts the terminal columns `s` occupies, skipping ANSI color sequences and OSC 8
+/// hyperlink wrappers.
+fn visible_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1B' {
+            width += c.width().unwrap_or(0);
+            continue;
+        }
+        match chars.next() {
+            // CSI sequences end with a letter, e.g. `\x1B[1;34m`.
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            // OSC sequences end with BEL.
+            Some(']') => {
+                for c in chars.by_ref() {
+                    if c == '\x07' {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    width
+}
+
+/// Clips `line` to at most `max_width` terminal columns, ending it with `…` when cut.
+///
+/// Escape sequences are kept even past the cut, so colors and hyperlinks still close.
+fn clip_line(line: String, max_width: Option<usize>) -> String {
+    let Some(max_width) = max_width else {
+        return line;
+    };
+    if visible_width(&line) <= max_width {
+        return line;
+    }
+    // One column is left for the ellipsis.
+    let budget = max_width.saturating_sub(1);
+    let mut clipped = String::with_capacity(line.len());
+    let mut width = 0;
+    let mut cut = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1B' {
+            clipped.push(c);
+            // Copy the whole sequence: CSI ends with a letter and OSC with BEL.
+            match chars.next() {
+                Some(kind @ ('[' | ']')) => {
+                    clipped.push(kind);
+                    for c in chars.by_ref() {
+                        clipped.push(c);
+                        let ends = if kind == '[' { c.is_ascii_alphabetic() } else { c == '\x07' };
+                        if ends {
+                            break;
+                        }
+                    }
+                }
+                Some(other) => clipped.push(other),
+                None => {}
+            }
+            continue;
+        }
+        let char_width = c.width().unwrap_or(0);
+        if !cut && width + char_width <= budget {
+            clipped.push(c);
+            width += char_width;
+        } else if !cut {
+            cut = true;
+            if max_width > 0 {
+                clipped.push('…');
+            }
+        }
+    }
+    clipped
+}
+
+/// Shortens `path` to at most `max_width` columns for --path-shorten, keeping its first and
+/// last components whole.
+///
+/// The directories in between are first cut to their initial, from the left, then
+/// replaced by a single `…` until the path fits. A path with nothing in between to
+/// shorten is returned as is, even when too wide.
+fn shorten_path(path: &str, max_width: usize) -> String {
+    if visible_width(path) <= max_width {
+        return path.to_string();
+    }
+    let separator = std::path::MAIN_SEPARATOR_STR;
+    let parts: Vec<&str> = path.split(separator).collect();
+    // The first component is kept along with any leading "" (root) or "." before it.
+    let first = parts.iter().position(|part| !part.is_empty() && *part != ".").map_or(0, |i| i + 1);
+    let last = parts.len() - 1;
+    if first >= last {
+        return path.to_string();
+    }
+    let join = |middle: &[String]| {
+        let mut all: Vec<&str> = parts[..first].to_vec();
+        all.extend(middle.iter().map(String::as_str));
+        all.push(parts[last]);
+        all.join(separator)
+    };
+
+    let mut middle: Vec<String> = parts[first..last].iter().map(|part| part.to_string()).collect();
+    for i in 0..middle.len() {
+        // A dot directory keeps its dot, so `.config` becomes `.c`.
+        let keep = if middle[i].starts_with('.') { 2 } else { 1 };
+        middle[i] = middle[i].chars().take(keep).collect();
+        let shortened = join(&middle);
+        if visible_width(&shortened) <= max_width {
+            return shortened;
+        }
+    }
+    for dropped in 1..middle.len() {
+        let mut rest = vec!["…".to_string()];
+        rest.extend_from_slice(&middle[dropped..]);
+        let shortened = join(&rest);
+        if visible_width(&shortened) <= max_width {
+            return shortened;
+        }
+    }
+    join(&["…".to_string()])
+}
+
+/// Finds the entries whose name matches a sibling's when case is ignored, which would
+/// collide on a case-insensitive filesystem, for --warn-case-collisions.
+fn find_case_collisions(entries: &[DirEntry]) -> HashSet<PathBuf> {
+    let mut siblings: HashMap<(&Path, String), Vec<&Path>> = HashMap::new();
+    for entry in entries {
+        let parent = entry.path().parent().unwrap_or(Path::new(""));
+        let folded_name = entry.file_name().to_string_lossy().to_lowercase();
+        siblings.entry((parent, folded_name)).or_default().push(entry.path());
+    }
+    siblings
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .flatten()
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// Returns where the symlink `entry` leads for --canonical: its fully resolved path, or the
+/// link's own target, marked as unresolved, when that can't be resolved. Other entries
+/// have none.
+fn link_target(entry: &DirEntry) -> Option<String> {
+    if !entry.path_is_symlink() {
+        return None;
+    }
+    match fs::canonicalize(entry.path()) {
+        Ok(target) => Some(utils::display_os_str(target.as_os_str()).into_owned()),
+        Err(_) => {
+            let target = fs::read_link(entry.path()).ok()?;
+            Some(format!("{} (unresolved)", utils::display_os_str(target.as_os_str())))
+        }
+    }
+}
+
+/// Extracts the offending path (if any) and a short description from a walk error.
+fn describe_walk_error(err: &ignore::Error) -> (Option<PathBuf>, String) {
+    match err {
+        ignore::Error::WithPath { path, err } => (Some(path.clone()), describe_walk_error(err).1),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            describe_walk_error(err)
+        }
+        ignore::Error::Loop { child, .. } => (Some(child.clone()), "filesystem loop".to_string()),
+        ignore::Error::Io(io_err) if io_err.kind() == io::ErrorKind::PermissionDenied => {
+            (None, "permission denied".to_string())
+        }
+        other => (None, other.to_string()),
+    }
+}
+
+/// Formats an absolute path for display relative to `base`.
+///
+/// Paths that don't live under `base` fall back to their absolute form.
+fn format_display_path(absolute: &Path, base: &Path) -> String {
+    match absolute.strip_prefix(base) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => utils::display_os_str(relative.as_os_str()).into_owned(),
+        Err(_) => utils::display_os_str(absolute.as_os_str()).into_owned(),
+    }
+}
 
 /// Builds tree structure information for proper connector display
-/// Returns a map from entry index to (prefix, connector) tuple  
+/// Returns a map from row index to (prefix, connector) tuple
+///
+/// Collapsed directories take up no level of their own, so depths and parents are
+/// measured between the directories that are actually shown.
 fn build_tree_info(
-    entries: &[ignore::DirEntry],
-) -> std::collections::HashMap<usize, (String, String)> {
-    use std::collections::HashMap;
-
+    rows: &[TreeRow],
+    collapsed: &HashSet<PathBuf>,
+) -> HashMap<usize, (String, String)> {
     let mut tree_info = HashMap::new();
+    let depth_of = |row: &TreeRow| {
+        let hidden_levels = row.parent().ancestors().filter(|dir| collapsed.contains(*dir)).count();
+        row.depth() - hidden_levels
+    };
 
-    for (index, entry) in entries.iter().enumerate() {
-        let depth = entry.depth();
+    for (index, row) in rows.iter().enumerate() {
+        let depth = depth_of(row);
         let mut prefix = String::new();
 
-        // Build prefix by walking up the tree and checking each ancestor
-        let current_path = entry.path();
-
         // For each depth level from 1 to current depth - 1
         for level in 1..depth {
-            // Find the ancestor directory at this level
+            // Find the ancestor directory at this level, walking up from the row's parent
             let ancestor_path = {
-                let mut path = current_path;
-                for _ in level..depth {
+                let mut path = parent_of(row, collapsed);
+                for _ in level..depth - 1 {
                     if let Some(parent) = path.parent() {
-                        path = parent;
+                        path = visible_dir(parent, collapsed);
                     }
                 }
                 path
             };
 
             // Check if this ancestor has more siblings coming after it
-            let has_more_siblings = entries.iter().enumerate().any(|(later_index, later_entry)| {
-                later_index > index && // Must come after current entry
-                    later_entry.depth() == level && // Same depth as ancestor
-                    later_entry.path().parent() == ancestor_path.parent() // Same parent as ancestor
+            let ancestor_parent = ancestor_path.parent().map(|p| visible_dir(p, collapsed));
+            let has_more_siblings = rows.iter().enumerate().any(|(later_index, later_row)| {
+                later_index > index && // Must come after current row
+                    depth_of(later_row) == level && // Same depth as ancestor
+                    Some(parent_of(later_row, collapsed)) == ancestor_parent // Same parent as ancestor
             });
 
             if has_more_siblings {
@@ -319,11 +1517,11 @@ fn build_tree_info(
             }
         }
 
-        // Determine connector for this entry (├── vs └──)
-        let is_last_sibling = !entries.iter().enumerate().any(|(later_index, later_entry)| {
-            later_index > index && // Must come after current entry
-                later_entry.depth() == depth && // Same depth
-                later_entry.path().parent() == entry.path().parent() // Same parent
+        // Determine connector for this row (├── vs └──)
+        let is_last_sibling = !rows.iter().enumerate().any(|(later_index, later_row)| {
+            later_index > index && // Must come after current row
+                depth_of(later_row) == depth && // Same depth
+                parent_of(later_row, collapsed) == parent_of(row, collapsed) // Same parent
         });
 
         let connector = if is_last_sibling { "└──" } else { "├──" };
@@ -332,3 +1530,33 @@ fn build_tree_info(
 
     tree_info
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_fit_level() -> anyhow::Result<()> {
+        // Two entries at the first level, three down to the second, four down to the third.
+        let temp_dir = tempfile::tempdir()?;
+        fs::create_dir_all(temp_dir.path().join("a/b"))?;
+        fs::write(temp_dir.path().join("a/b/c.txt"), "")?;
+        fs::write(temp_dir.path().join("top.txt"), "")?;
+        let root = temp_dir.path().to_str().unwrap();
+
+        let args = ViewArgs::parse_from(["view", root]);
+        // Three lines go to the root, the blank line, and the summary.
+        assert_eq!(fit_level(&args, 3 + 4), 3);
+        assert_eq!(fit_level(&args, 3 + 3), 2);
+        assert_eq!(fit_level(&args, 3 + 2), 1);
+        // Even the first level overflows, so it falls back to 1.
+        assert_eq!(fit_level(&args, 3 + 1), 1);
+        // Going past the deepest level adds nothing.
+        assert_eq!(fit_level(&args, 100), 3);
+
+        let args = ViewArgs::parse_from(["view", root, "-L", "2"]);
+        assert_eq!(fit_level(&args, 100), 2);
+        Ok(())
+    }
+}
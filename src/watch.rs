@@ -0,0 +1,80 @@
+//! Provides filesystem watching for the live-refresh (`--watch`) modes.
+//!
+//! This module wraps the `notify` crate to watch a root directory recursively
+//! and collapse bursts of events into a single, debounced change notification.
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// The ANSI sequence that clears the screen and moves the cursor to the top-left.
+pub const CLEAR_SCREEN: &str = "\x1B[2J\x1B[H";
+
+/// Starts a synchronized update: terminals that support it hold the display until
+/// [`END_SYNCHRONIZED_UPDATE`], so a redraw appears all at once. Others ignore it.
+pub const BEGIN_SYNCHRONIZED_UPDATE: &str = "\x1B[?2026h";
+
+/// Ends a synchronized update started with [`BEGIN_SYNCHRONIZED_UPDATE`].
+pub const END_SYNCHRONIZED_UPDATE: &str = "\x1B[?2026l";
+
+/// A recursive watcher over a directory tree with debounced change detection.
+pub struct FsWatcher {
+    // The watcher must be kept alive for events to keep arriving.
+    _watcher: RecommendedWatcher,
+    rx: Receiver<notify::Result<notify::Event>>,
+    debounce: Duration,
+}
+
+impl FsWatcher {
+    /// Starts watching `root` recursively.
+    ///
+    /// Events arriving within `debounce` of each other are treated as one change.
+    pub fn new(root: &Path, debounce: Duration) -> anyhow::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        watcher.watch(root, RecursiveMode::Recursive)?;
+        Ok(Self { _watcher: watcher, rx, debounce })
+    }
+
+    /// Blocks until a change is observed and the debounce window has gone quiet.
+    ///
+    /// Returns `false` if the watcher has shut down and no more changes will arrive.
+    pub fn wait_for_change(&self) -> bool {
+        loop {
+            match self.rx.recv() {
+                Ok(event) if is_relevant(&event) => break,
+                Ok(_) => continue,
+                Err(_) => return false,
+            }
+        }
+        self.drain();
+        true
+    }
+
+    /// Returns `true` if a change has been observed since the last call, without blocking
+    /// unless a change is pending (in which case it waits out the debounce window).
+    pub fn poll_change(&self) -> bool {
+        let mut changed = false;
+        while let Ok(event) = self.rx.try_recv() {
+            changed |= is_relevant(&event);
+        }
+        if changed {
+            self.drain();
+        }
+        changed
+    }
+
+    /// Consumes events until none arrive for a full debounce interval.
+    fn drain(&self) {
+        while self.rx.recv_timeout(self.debounce).is_ok() {}
+    }
+}
+
+/// Filters out access-only events, which our own directory walks can trigger.
+fn is_relevant(event: &notify::Result<notify::Event>) -> bool {
+    match event {
+        Ok(event) => !event.kind.is_access(),
+        Err(_) => true,
+    }
+}
@@ -164,6 +164,65 @@ fn test_git_status_flag() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn test_git_status_marks_dirs_with_changes() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let git = |args: &[&str]| Command::new("git").args(args).current_dir(temp_path).output();
+
+    git(&["init"])?;
+    git(&["config", "user.email", "test@example.com"])?;
+    git(&["config", "user.name", "Test User"])?;
+    fs::create_dir_all(temp_path.join("clean"))?;
+    fs::create_dir_all(temp_path.join("dirty/deep"))?;
+    fs::write(temp_path.join("clean/file.txt"), "")?;
+    fs::write(temp_path.join("dirty/deep/file.txt"), "")?;
+    git(&["add", "."])?;
+    git(&["commit", "-m", "initial"])?;
+    fs::write(temp_path.join("dirty/deep/file.txt"), "changed")?;
+
+    // The change is two levels down, below the cut-off, but still marks its directory.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-G", "-L", "1", "--color", "never"]).arg(temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("  ├── clean"))
+        .stdout(predicate::str::contains("* └── dirty"));
+
+    Ok(())
+}
+
+#[test]
+fn test_git_status_in_subdirectory() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let git = |args: &[&str]| Command::new("git").args(args).current_dir(temp_path).output();
+
+    git(&["init"])?;
+    git(&["config", "user.email", "test@example.com"])?;
+    git(&["config", "user.name", "Test User"])?;
+    fs::create_dir_all(temp_path.join("inside/sub"))?;
+    fs::write(temp_path.join("inside/sub/file.txt"), "")?;
+    fs::write(temp_path.join("outside.txt"), "")?;
+    git(&["add", "."])?;
+    git(&["commit", "-m", "initial"])?;
+    fs::write(temp_path.join("inside/sub/file.txt"), "changed")?;
+    fs::write(temp_path.join("outside.txt"), "changed")?;
+    fs::write(temp_path.join("inside/new.txt"), "")?;
+
+    // Only the subtree is scanned, with paths still keyed from the repository root.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-G", "--color", "never"]).arg(temp_path.join("inside"));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("? ├── new.txt"))
+        .stdout(predicate::str::contains("* └── sub"))
+        .stdout(predicate::str::contains("M     └── file.txt"))
+        .stdout(predicate::str::contains("outside.txt").not());
+
+    Ok(())
+}
+
 #[test]
 fn test_sort_by_name() -> Result<(), Box<dyn std::error::Error>> {
     let temp_dir = tempdir()?;
@@ -209,6 +268,58 @@ fn test_dirs_first_sorting() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn test_dirs_last_sorting() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("aaa_dir"))?;
+    fs::File::create(temp_dir.path().join("zzz_file.txt"))?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--dirs-last").arg(temp_dir.path());
+    let stdout = String::from_utf8(cmd.output()?.stdout)?;
+
+    // The file comes before the directory, despite alphabetical order
+    assert!(stdout.find("zzz_file.txt").unwrap() < stdout.find("aaa_dir").unwrap());
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--dirs-last", "--dirs-first"]).arg(temp_dir.path());
+    cmd.assert().failure();
+
+    // Dotfiles still lead their group.
+    fs::create_dir(temp_dir.path().join(".zzz_dotdir"))?;
+    fs::File::create(temp_dir.path().join(".zzz_dotfile"))?;
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--dirs-last", "--dotfiles-first", "-a"]).arg(temp_dir.path());
+    let stdout = String::from_utf8(cmd.output()?.stdout)?;
+    let positions: Vec<usize> = [".zzz_dotfile", "zzz_file.txt", ".zzz_dotdir", "aaa_dir"]
+        .iter()
+        .map(|name| stdout.find(name).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+    Ok(())
+}
+
+#[test]
+fn test_warn_case_collisions() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("README.md"), "")?;
+    fs::write(temp_dir.path().join("readme.md"), "")?;
+    fs::create_dir(temp_dir.path().join("docs"))?;
+    fs::write(temp_dir.path().join("docs/Readme.md"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--warn-case-collisions", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("README.md (case collision)\n"))
+        .stdout(predicate::str::contains("readme.md (case collision)\n"))
+        // Names only collide with their siblings.
+        .stdout(predicate::str::contains("Readme.md\n"));
+
+    Ok(())
+}
+
 #[test]
 fn test_natural_sorting() -> Result<(), Box<dyn std::error::Error>> {
     let temp_dir = tempdir()?;
@@ -282,6 +393,8 @@ fn test_sort_by_extension() -> Result<(), Box<dyn std::error::Error>> {
     fs::File::create(temp_dir.path().join("file.zzz"))?;
     fs::File::create(temp_dir.path().join("file.aaa"))?;
     fs::File::create(temp_dir.path().join("file.bbb"))?;
+    fs::File::create(temp_dir.path().join("a_file.bbb"))?;
+    fs::File::create(temp_dir.path().join("noext"))?;
 
     let mut cmd = Command::cargo_bin("lstr")?;
     cmd.arg("--sort").arg("extension").arg(temp_dir.path());
@@ -297,6 +410,10 @@ fn test_sort_by_extension() -> Result<(), Box<dyn std::error::Error>> {
     assert!(aaa_pos < bbb_pos);
     assert!(bbb_pos < zzz_pos);
 
+    // Extensionless files come first, and a shared extension falls back to the name.
+    assert!(stdout.find("noext").unwrap() < aaa_pos);
+    assert!(stdout.find("a_file.bbb").unwrap() < bbb_pos);
+
     Ok(())
 }
 
@@ -500,3 +617,1520 @@ fn test_deep_nested_tree() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_root_from_env_var() -> Result<(), Box<dyn std::error::Error>> {
+    let env_dir = tempdir()?;
+    fs::write(env_dir.path().join("from_env.txt"), "env")?;
+    let arg_dir = tempdir()?;
+    fs::write(arg_dir.path().join("from_arg.txt"), "arg")?;
+
+    // Without a path argument, LSTR_ROOT is used.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.env("LSTR_ROOT", env_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("from_env.txt"));
+
+    // An explicit path argument takes precedence over LSTR_ROOT.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.env("LSTR_ROOT", env_dir.path()).arg(arg_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("from_arg.txt"))
+        .stdout(predicate::str::contains("from_env.txt").not());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_dereference_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("target.bin"), vec![0u8; 2048])?;
+    std::os::unix::fs::symlink("target.bin", temp_dir.path().join("link.bin"))?;
+    std::os::unix::fs::symlink("missing.bin", temp_dir.path().join("broken.bin"))?;
+
+    // Without -H the link reports its own (tiny) size.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("-s").arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::is_match(r"link\.bin.*\(\d+ B\)").unwrap());
+
+    // With -H the link reports the target's size; broken links still render.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("-s").arg("-H").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"link\.bin.*\(2\.0 KiB\)").unwrap())
+        .stdout(predicate::str::contains("broken.bin"));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_classify_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("dir"))?;
+    fs::write(temp_dir.path().join("plain.txt"), "")?;
+    fs::write(temp_dir.path().join("run.sh"), "")?;
+    fs::set_permissions(temp_dir.path().join("run.sh"), fs::Permissions::from_mode(0o755))?;
+    std::os::unix::fs::symlink("plain.txt", temp_dir.path().join("link"))?;
+    let _listener = std::os::unix::net::UnixListener::bind(temp_dir.path().join("sock"))?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("-F").arg("-p").arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("── dir/"))
+        .stdout(predicate::str::contains("── run.sh*"))
+        .stdout(predicate::str::contains("── link@"))
+        .stdout(predicate::str::is_match(r"(?m)^s\S+ .*── sock=$").unwrap())
+        .stdout(predicate::str::is_match(r"(?m)── plain\.txt$").unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn test_sort_by_created() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("older.txt"), "old")?;
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    fs::write(temp_dir.path().join("newer.txt"), "new")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--sort").arg("created").arg(temp_dir.path());
+
+    let output = cmd.output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+
+    // Newest entries come first.
+    let newer_pos = stdout.find("newer.txt").unwrap();
+    let older_pos = stdout.find("older.txt").unwrap();
+    assert!(newer_pos < older_pos);
+
+    Ok(())
+}
+
+#[test]
+fn test_summary_only_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("a.txt"), "hello")?;
+    fs::create_dir(temp_dir.path().join("dir1"))?;
+    fs::write(temp_dir.path().join("dir1/b.txt"), "world")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--summary-only").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("a.txt").not())
+        .stdout(predicate::str::diff("1 directories, 2 files\n"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--summary-only").arg("-s").arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::diff("1 directories, 2 files, 10 B total\n"));
+
+    // Under the tree, plain -s leaves the summary as it was.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("-s").arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains(" total").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_git_status_position_name() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    Command::new("git").arg("init").current_dir(temp_path).output()?;
+    fs::write(temp_path.join("untracked.txt"), "untracked")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("-G").arg("--git-status-position").arg("name").arg(temp_path);
+
+    // The marker sits between the connector and the name.
+    cmd.assert().success().stdout(predicate::str::is_match(r"└── .*\?.*untracked\.txt").unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn test_relative_to_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let project = temp_dir.path().join("proj");
+    fs::create_dir_all(project.join("dir1"))?;
+    fs::write(project.join("dir1/b.txt"), "b")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--full-path").arg("--relative-to").arg(temp_dir.path()).arg(&project);
+    let nested = format!("proj{0}dir1{0}b.txt", std::path::MAIN_SEPARATOR);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("proj\n"))
+        .stdout(predicate::str::contains(nested));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_show_errors_inline() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let locked = temp_dir.path().join("locked");
+    fs::create_dir(&locked)?;
+    fs::write(locked.join("inside.txt"), "x")?;
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000))?;
+
+    // Privileged users can read the directory anyway, so there is nothing to report.
+    if fs::read_dir(&locked).is_ok() {
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755))?;
+        return Ok(());
+    }
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--show-errors").arg("inline").arg(temp_dir.path());
+    let assert = cmd.assert();
+
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755))?;
+    assert
+        .success()
+        .stdout(predicate::str::contains("locked [permission denied]"))
+        .stderr(predicate::str::is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_count_by_extension() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("a.rs"), "12")?;
+    fs::write(temp_dir.path().join("b.rs"), "34")?;
+    fs::create_dir(temp_dir.path().join("src"))?;
+    fs::write(temp_dir.path().join("src/c.rs"), "56")?;
+    fs::write(temp_dir.path().join("notes.md"), "x")?;
+    fs::write(temp_dir.path().join("Makefile"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--count-by-extension").arg(temp_dir.path());
+
+    let output = cmd.output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(!stdout.contains("├──"), "tree should not be printed");
+    assert!(predicate::str::is_match(r"rs\s+3\s+6 B").unwrap().eval(&stdout));
+    assert!(predicate::str::is_match(r"\(none\)\s+1\s+0 B").unwrap().eval(&stdout));
+    // Highest count first; ties are broken by extension name.
+    let rs_pos = stdout.find("rs ").unwrap();
+    let none_pos = stdout.find("(none)").unwrap();
+    let md_pos = stdout.find("md ").unwrap();
+    assert!(rs_pos < none_pos);
+    assert!(none_pos < md_pos);
+    assert!(stdout.contains("3 extensions, 5 files"));
+
+    Ok(())
+}
+
+#[test]
+fn test_bytes_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("data.bin"), vec![0u8; 1536])?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("-s").arg("--bytes").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("data.bin (1536)"))
+        .stdout(predicate::str::contains("1536 total"))
+        .stdout(predicate::str::contains("KiB").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_lstrignore_file() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join(".lstrignore"), "build/\n*.log\n")?;
+    fs::create_dir(temp_dir.path().join("build"))?;
+    fs::write(temp_dir.path().join("build/out.o"), "")?;
+    fs::write(temp_dir.path().join("debug.log"), "")?;
+    fs::write(temp_dir.path().join("main.rs"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("main.rs"))
+        .stdout(predicate::str::contains("build").not())
+        .stdout(predicate::str::contains("debug.log").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_dim_beyond_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("top"))?;
+    fs::write(temp_dir.path().join("top/deep.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.env_remove("LS_COLORS");
+    cmd.arg("--dim-beyond").arg("1").arg("--color").arg("always").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[2mdeep.txt"))
+        .stdout(predicate::str::contains("\x1b[2mtop").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_max_files_per_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    for name in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+        fs::write(temp_dir.path().join(name), "")?;
+    }
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--max-files-per-dir").arg("2").arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("├── a.txt"))
+        .stdout(predicate::str::contains("├── b.txt"))
+        .stdout(predicate::str::contains("└── ... (2 more)"))
+        .stdout(predicate::str::contains("c.txt").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_git_submodules_option() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let library = temp_dir.path().join("library");
+    let project = temp_dir.path().join("project");
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        Command::new("git")
+            .args(["-c", "user.email=test@example.com", "-c", "user.name=Test User"])
+            .args(["-c", "protocol.file.allow=always"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+    };
+
+    fs::create_dir(&library)?;
+    git(&library, &["init"])?;
+    fs::write(library.join("lib.txt"), "v1")?;
+    git(&library, &["add", "lib.txt"])?;
+    git(&library, &["commit", "-m", "library"])?;
+
+    fs::create_dir(&project)?;
+    git(&project, &["init"])?;
+    fs::write(project.join("main.txt"), "")?;
+    git(&project, &["submodule", "add", library.to_str().unwrap(), "vendor"])?;
+    git(&project, &["add", "main.txt"])?;
+    git(&project, &["commit", "-m", "project"])?;
+    fs::write(project.join("vendor/lib.txt"), "v2")?;
+
+    // By default the submodule is one entry, marked S, and its files are left out.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-G", "--color", "never"]).arg(&project);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"S\s+.*vendor").unwrap())
+        .stdout(predicate::str::contains("lib.txt").not());
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-G", "--git-submodules", "ignore", "--color", "never"]).arg(&project);
+    cmd.assert().success().stdout(predicate::str::contains("lib.txt").not());
+
+    // With show, lstr descends into it and marks the changes inside.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-G", "--git-submodules", "show", "--color", "never"]).arg(&project);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("vendor"))
+        .stdout(predicate::str::is_match(r"M\s+.*lib\.txt").unwrap());
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--git-submodules").arg("descend").arg(&project);
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn test_compact_files_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("a.txt"), "")?;
+    fs::write(temp_dir.path().join("b.txt"), "")?;
+    fs::create_dir(temp_dir.path().join("sub"))?;
+    fs::write(temp_dir.path().join("sub/c.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--compact-files").arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("├── a.txt  b.txt\n"))
+        .stdout(predicate::str::contains("└── sub\n    └── c.txt"))
+        .stdout(predicate::str::contains("1 directories, 3 files"));
+
+    Ok(())
+}
+
+#[test]
+fn test_sort_ties_are_deterministic() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    for name in ["delta.txt", "Alpha.txt", "charlie.txt", "bravo.txt"] {
+        fs::write(temp_dir.path().join(name), "same")?;
+    }
+
+    let run = || -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("lstr")?;
+        cmd.arg("--sort").arg("size").arg("--color").arg("never").arg(temp_dir.path());
+        Ok(cmd.assert().success().get_output().stdout.clone())
+    };
+    let first = run()?;
+    assert_eq!(first, run()?);
+
+    let output = String::from_utf8(first)?;
+    let alpha = output.find("Alpha.txt").unwrap();
+    let bravo = output.find("bravo.txt").unwrap();
+    let charlie = output.find("charlie.txt").unwrap();
+    let delta = output.find("delta.txt").unwrap();
+    assert!(alpha < bravo && bravo < charlie && charlie < delta);
+
+    Ok(())
+}
+
+#[test]
+fn test_root_header() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("file.txt"), "")?;
+    let canonical = fs::canonicalize(temp_dir.path())?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--color").arg("never").current_dir(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!("{}\n", canonical.display())));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--root-label").arg("my-project").arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::starts_with("my-project\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_since_commit_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let git = |args: &[&str]| Command::new("git").args(args).current_dir(temp_path).output();
+
+    git(&["init"])?;
+    git(&["config", "user.email", "test@example.com"])?;
+    git(&["config", "user.name", "Test User"])?;
+    fs::create_dir(temp_path.join("src"))?;
+    fs::write(temp_path.join("src/main.rs"), "fn main() {}")?;
+    fs::write(temp_path.join("src/lib.rs"), "")?;
+    fs::write(temp_path.join("README.md"), "")?;
+    git(&["add", "."])?;
+    git(&["commit", "-m", "initial"])?;
+
+    fs::write(temp_path.join("src/main.rs"), "fn main() { println!(); }")?;
+    fs::write(temp_path.join("new.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--since-commit", "HEAD", "--git-status-position", "name", "--color", "never"]);
+    cmd.arg(temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("M main.rs"))
+        .stdout(predicate::str::contains("? new.txt"))
+        .stdout(predicate::str::contains("src"))
+        .stdout(predicate::str::contains("lib.rs").not())
+        .stdout(predicate::str::contains("README.md").not());
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--since-commit").arg("no-such-rev").arg(temp_path);
+    cmd.assert().failure().stderr(predicate::str::contains("not a valid git revision"));
+
+    Ok(())
+}
+
+#[test]
+fn test_size_color_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("big.bin"), vec![0u8; 10_000])?;
+    fs::write(temp_dir.path().join("tiny.txt"), "x")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.env_remove("LS_COLORS");
+    cmd.arg("--size-color").arg("-s").arg("--color").arg("always").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[31mbig.bin"))
+        .stdout(predicate::str::contains("\x1b[2mtiny.txt"))
+        .stdout(predicate::str::contains("(9.8 KiB)"));
+
+    Ok(())
+}
+
+#[test]
+fn test_no_summary_newline_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("file.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("file.txt\n\n0 directories, 1 files"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--no-summary-newline").arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("file.txt\n0 directories, 1 files"));
+
+    Ok(())
+}
+
+#[test]
+fn test_format_template() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("file.txt"), vec![0u8; 2048])?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--format").arg("[{size:>8}] {name}").arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("[ 2.0 KiB] └── file.txt\n"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--format").arg("{nope} {name}").arg(temp_dir.path());
+    cmd.assert().failure().stderr(predicate::str::contains("unknown placeholder '{nope}'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_checksum_column() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("abc.txt"), "abc")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--checksum").arg("md5").arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("900150983cd24fb0d6963f7d28e17f72 └── abc.txt"));
+
+    Ok(())
+}
+
+#[test]
+fn test_loc_column() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("a.txt"), "a\nb\nc\n")?;
+    fs::write(temp_dir.path().join("big.txt"), "line\n".repeat(12))?;
+    fs::write(temp_dir.path().join("data.bin"), b"\0\x01\x02\n")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--loc").arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(" 3 ├── a.txt"))
+        .stdout(predicate::str::contains("12 ├── big.txt"))
+        .stdout(predicate::str::contains("   └── data.bin"))
+        .stdout(predicate::str::contains("0 directories, 3 files, 15 lines"));
+
+    Ok(())
+}
+
+#[test]
+fn test_max_total_size() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    for name in ["a.bin", "b.bin", "c.bin"] {
+        fs::write(temp_dir.path().join(name), vec![0u8; 1024])?;
+    }
+
+    // b.bin takes the total past 1.5 KiB, so it is the last file shown.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--max-total-size").arg("1.5K").arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("b.bin"))
+        .stdout(predicate::str::contains("c.bin").not())
+        .stdout(predicate::str::contains("... (1 more, stopped past 1.5 KiB of files)"))
+        .stdout(predicate::str::contains("0 directories, 2 files"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--max-total-size").arg("lots").arg(temp_dir.path());
+    cmd.assert().failure().stderr(predicate::str::contains("invalid size 'lots'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_reverse_within_dirs_only() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("c_dir"))?;
+    fs::write(temp_dir.path().join("c_dir/x.txt"), "")?;
+    fs::write(temp_dir.path().join("c_dir/y.txt"), "")?;
+    fs::write(temp_dir.path().join("a.txt"), "")?;
+    fs::write(temp_dir.path().join("b.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--reverse-within-dirs-only").arg("--color").arg("never").arg(temp_dir.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    // Each directory's children come out reversed, still nested under it.
+    let position = |name: &str| output.find(name).unwrap();
+    assert!(position("c_dir") < position("y.txt"));
+    assert!(position("y.txt") < position("x.txt"));
+    assert!(position("x.txt") < position("b.txt"));
+    assert!(position("b.txt") < position("a.txt"));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_output_tsv() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("plain.txt"), "abc")?;
+    fs::write(temp_dir.path().join("tab\tname.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--output", "tsv", "--fields", "name,size", "--color", "never"]);
+    cmd.arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("name\tsize\n"))
+        .stdout(predicate::str::contains("plain.txt\t3 B\n"))
+        .stdout(predicate::str::contains("tab\\tname.txt\t0 B\n"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--output", "tsv", "--fields", "name", "--no-header", "--strict"]);
+    cmd.arg(temp_dir.path());
+    cmd.assert().failure().stderr(predicate::str::contains("--strict"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--output", "tsv"]).arg(temp_dir.path());
+    cmd.assert().failure().stderr(predicate::str::contains("--fields"));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_output_tree_json() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir_all(temp_dir.path().join("a/empty"))?;
+    fs::write(temp_dir.path().join("a/say \"hi\".txt"), "")?;
+    fs::write(temp_dir.path().join("b.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.current_dir(temp_dir.path()).args(["--output", "tree-json", "."]);
+    cmd.assert().success().stdout(concat!(
+        "[\n",
+        "  {\"type\":\"directory\",\"name\":\".\",\"contents\":[\n",
+        "    {\"type\":\"directory\",\"name\":\"a\",\"contents\":[\n",
+        "      {\"type\":\"directory\",\"name\":\"empty\",\"contents\":[\n",
+        "      ]},\n",
+        "      {\"type\":\"file\",\"name\":\"say \\\"hi\\\".txt\"}\n",
+        "    ]},\n",
+        "    {\"type\":\"file\",\"name\":\"b.txt\"}\n",
+        "  ]}\n",
+        ",\n",
+        "  {\"type\":\"report\",\"directories\":2,\"files\":2}\n",
+        "]\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_prune_matching() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir_all(temp_dir.path().join("cache/nested"))?;
+    fs::write(temp_dir.path().join("cache/nested/old.tmp"), "")?;
+    fs::create_dir(temp_dir.path().join("docs"))?;
+    fs::write(temp_dir.path().join("docs/draft.tmp"), "")?;
+    fs::write(temp_dir.path().join("docs/guide.md"), "")?;
+    fs::create_dir(temp_dir.path().join("empty"))?;
+    fs::write(temp_dir.path().join("top.tmp"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg(temp_dir.path()).args(["--prune-matching", "*.tmp", "--color", "never"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("top.tmp").not())
+        .stdout(predicate::str::contains("draft.tmp").not())
+        .stdout(predicate::str::contains("old.tmp").not())
+        .stdout(predicate::str::contains("cache").not())
+        .stdout(predicate::str::contains("guide.md"))
+        .stdout(predicate::str::contains("empty"))
+        .stdout(predicate::str::contains("2 directories, 1 files"));
+
+    Ok(())
+}
+
+#[test]
+fn test_count_hidden() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir_all(temp_dir.path().join("a/b"))?;
+    fs::write(temp_dir.path().join("a/b/deep.txt"), "")?;
+    fs::write(temp_dir.path().join("top.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-L", "1", "--count-hidden"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1 directories, 1 files (2 more below depth limit)"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--count-hidden").arg(temp_dir.path());
+    cmd.assert().failure().stderr(predicate::str::contains("--level"));
+
+    Ok(())
+}
+
+#[test]
+fn test_one_filesystem() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("sub"))?;
+    fs::write(temp_dir.path().join("sub/inner.txt"), "")?;
+
+    // Everything in a temporary directory is on one filesystem, so nothing is cut.
+    for flag in ["-x", "--one-filesystem", "--mount-boundary"] {
+        let mut cmd = Command::cargo_bin("lstr")?;
+        cmd.arg(flag).arg(temp_dir.path());
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("inner.txt"))
+            .stdout(predicate::str::contains("1 directories, 1 files"));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_show_size_always() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("sub"))?;
+    fs::write(temp_dir.path().join("file.txt"), "abc")?;
+    let dir_size = fs::metadata(temp_dir.path().join("sub"))?.len();
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-s", "--bytes", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("sub (").not());
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-s", "--bytes", "--show-size-always", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("sub ({dir_size})")))
+        .stdout(predicate::str::contains("file.txt (3)"))
+        // The total still counts files only.
+        .stdout(predicate::str::contains(", 3 total"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--show-size-always").arg(temp_dir.path());
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn test_entry_limit_per_level() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("a"))?;
+    for name in ["x1.txt", "x2.txt", "x3.txt"] {
+        fs::write(temp_dir.path().join("a").join(name), "")?;
+    }
+    fs::create_dir(temp_dir.path().join("b"))?;
+    fs::write(temp_dir.path().join("b/y1.txt"), "")?;
+    fs::write(temp_dir.path().join("c.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--entry-limit-per-level", "2", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("x2.txt"))
+        .stdout(predicate::str::contains("x3.txt").not())
+        .stdout(predicate::str::contains("y1.txt").not())
+        .stdout(predicate::str::contains("c.txt").not())
+        .stdout(predicate::str::contains("... (3 more past 2 entries per level)"))
+        .stdout(predicate::str::contains("2 directories, 2 files"));
+
+    Ok(())
+}
+
+#[test]
+fn test_depth_summary() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir_all(temp_dir.path().join("a/b"))?;
+    fs::write(temp_dir.path().join("a/b/deep.txt"), "")?;
+    fs::write(temp_dir.path().join("a/one.txt"), "")?;
+    fs::write(temp_dir.path().join("top.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--depth-summary").arg("--color").arg("never").arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains(
+        "2 directories, 3 files\ndepth 1: 2, depth 2: 2, depth 3: 1\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_reverse_files_only() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("a_dir"))?;
+    fs::create_dir(temp_dir.path().join("b_dir"))?;
+    fs::write(temp_dir.path().join("a.txt"), "")?;
+    fs::write(temp_dir.path().join("b.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--reverse-files").arg("--color").arg("never").arg(temp_dir.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    let positions: Vec<usize> = ["a_dir", "b_dir", "b.txt", "a.txt"]
+        .iter()
+        .map(|name| output.find(name).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+    Ok(())
+}
+
+#[test]
+fn test_total_human_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("file.bin"), vec![0u8; 2048])?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-s", "--bytes", "--total-human", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("file.bin (2048)"))
+        .stdout(predicate::str::contains("2.0 KiB total"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-s", "--total-human", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("2.0 KiB total"));
+
+    Ok(())
+}
+
+#[test]
+fn test_sort_by_child_count() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("few"))?;
+    fs::write(temp_dir.path().join("few/one.txt"), "")?;
+    fs::create_dir(temp_dir.path().join("many"))?;
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        fs::write(temp_dir.path().join("many").join(name), "")?;
+    }
+    fs::write(temp_dir.path().join("file.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--sort", "child-count", "-L", "1", "--color", "never"]).arg(temp_dir.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    let many = output.find("many").unwrap();
+    let few = output.find("few").unwrap();
+    let file = output.find("file.txt").unwrap();
+    assert!(many < few && few < file);
+
+    Ok(())
+}
+
+#[test]
+fn test_sort_by_child_count_skips_hidden() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("dotted"))?;
+    for name in [".a", ".b", ".c", "one.txt"] {
+        fs::write(temp_dir.path().join("dotted").join(name), "")?;
+    }
+    fs::create_dir(temp_dir.path().join("plain"))?;
+    fs::write(temp_dir.path().join("plain/one.txt"), "")?;
+    fs::write(temp_dir.path().join("plain/two.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--sort", "child-count", "--color", "never"]).arg(temp_dir.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    // Only the entries listed are counted, so the dotfiles don't put "dotted" first.
+    assert!(output.find("plain").unwrap() < output.find("dotted").unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn test_collapse_single_child() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir_all(temp_dir.path().join("a/b/c"))?;
+    fs::write(temp_dir.path().join("a/b/c/inner.txt"), "")?;
+    fs::write(temp_dir.path().join("top.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--collapse-single-child", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("├── a/b/c\n│   └── inner.txt\n└── top.txt"))
+        .stdout(predicate::str::contains("3 directories, 2 files"));
+
+    Ok(())
+}
+
+#[test]
+fn test_time_column() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("fresh.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--time", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::is_match(r"\n +\d+s └── fresh\.txt\n")?);
+
+    Ok(())
+}
+
+#[test]
+fn test_git_only_changed_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let git = |args: &[&str]| Command::new("git").args(args).current_dir(temp_path).output();
+
+    git(&["init"])?;
+    git(&["config", "user.email", "test@example.com"])?;
+    git(&["config", "user.name", "Test User"])?;
+    fs::create_dir(temp_path.join("src"))?;
+    fs::write(temp_path.join("src/main.rs"), "fn main() {}")?;
+    fs::write(temp_path.join("src/lib.rs"), "")?;
+    fs::create_dir(temp_path.join("docs"))?;
+    fs::write(temp_path.join("docs/guide.md"), "")?;
+    git(&["add", "."])?;
+    git(&["commit", "-m", "initial"])?;
+
+    fs::write(temp_path.join("src/main.rs"), "fn main() { println!(); }")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--git-only-changed", "--color", "never"]).arg(temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("M "))
+        .stdout(predicate::str::contains("main.rs"))
+        .stdout(predicate::str::contains("src"))
+        .stdout(predicate::str::contains("lib.rs").not())
+        .stdout(predicate::str::contains("docs").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_max_width_clips_lines() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("a_really_long_file_name.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--max-width", "20", "--color", "never"]).arg(temp_dir.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    assert!(output.contains("└── a_really_long_f…\n"));
+    assert!(output.lines().all(|line| line.chars().count() <= 20));
+
+    Ok(())
+}
+
+#[test]
+fn test_path_shorten_abbreviates_middle_directories() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir_all(temp_dir.path().join("alpha/beta/gamma/delta"))?;
+    fs::write(temp_dir.path().join("alpha/beta/gamma/delta/file.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.current_dir(temp_dir.path());
+    cmd.args(["--full-path", "--path-shorten", "20", "--color", "never", "."]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    // Initials alone leave "./alpha/b/g/d/file.txt" too wide, so "…" replaces some.
+    assert!(output.contains("── ./alpha/…/d/file.txt\n"));
+    assert!(output.contains("── ./alpha/b/g/delta\n"));
+    assert!(output.contains("── ./alpha/beta\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_section_spacing_separates_top_level_subtrees() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("a_dir"))?;
+    fs::write(temp_dir.path().join("a_dir/inner.txt"), "")?;
+    fs::create_dir(temp_dir.path().join("b_dir"))?;
+    fs::write(temp_dir.path().join("c.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--section-spacing", "--color", "never"]).arg(temp_dir.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    // Only a_dir has a subtree to close off; the empty b_dir runs straight into c.txt.
+    assert!(output.contains("inner.txt\n\n├── b_dir\n└── c.txt\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_no_matching_entries_notice() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("file.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-d", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("(no matching entries)\n\n0 directories, 0 files"));
+
+    Ok(())
+}
+
+#[test]
+fn test_file_sort_size_within_dirs() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("b_dir"))?;
+    fs::create_dir(temp_dir.path().join("a_dir"))?;
+    fs::write(temp_dir.path().join("small.txt"), "x")?;
+    fs::write(temp_dir.path().join("big.txt"), "x".repeat(100))?;
+    fs::write(temp_dir.path().join("medium.txt"), "x".repeat(10))?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--file-sort", "size", "--color", "never"]).arg(temp_dir.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    let positions: Vec<usize> = ["a_dir", "b_dir", "big.txt", "medium.txt", "small.txt"]
+        .iter()
+        .map(|name| output.find(name).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+    Ok(())
+}
+
+#[test]
+fn test_dirs_by_name_with_child_count_sort() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("b_dir"))?;
+    fs::write(temp_dir.path().join("b_dir/big.txt"), "x".repeat(100))?;
+    fs::create_dir(temp_dir.path().join("a_dir"))?;
+    fs::write(temp_dir.path().join("notes.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--sort", "child-count", "--dirs-by-name", "-L", "1", "--color", "never"])
+        .arg(temp_dir.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    // By child count alone, b_dir would come first.
+    let positions: Vec<usize> =
+        ["a_dir", "b_dir", "notes.txt"].iter().map(|name| output.find(name).unwrap()).collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+    Ok(())
+}
+
+#[test]
+fn test_highlight_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("TODO.md"), "")?;
+    fs::write(temp_dir.path().join("notes.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--highlight", "TODO", "--color", "always"]).arg(temp_dir.path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    // Both files are still listed, but only the match gets the bright yellow background.
+    let todo_line = output.lines().find(|line| line.contains("TODO.md")).unwrap();
+    let notes_line = output.lines().find(|line| line.contains("notes.txt")).unwrap();
+    assert!(todo_line.contains("103"));
+    assert!(!notes_line.contains("103"));
+
+    Ok(())
+}
+
+#[test]
+fn test_collapse_under_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("big"))?;
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        fs::write(temp_dir.path().join("big").join(name), "")?;
+    }
+    fs::create_dir(temp_dir.path().join("small"))?;
+    fs::write(temp_dir.path().join("small/only.txt"), "1234")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--collapse-under", "2", "--bytes", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("a.txt"))
+        .stdout(predicate::str::contains("small (1 files, 4)"))
+        .stdout(predicate::str::contains("only.txt").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_git_status_shows_rename_source() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let git = |args: &[&str]| Command::new("git").args(args).current_dir(temp_path).output();
+
+    git(&["init"])?;
+    git(&["config", "user.email", "test@example.com"])?;
+    git(&["config", "user.name", "Test User"])?;
+    fs::write(temp_path.join("old_name.txt"), "some content that is kept\n")?;
+    git(&["add", "."])?;
+    git(&["commit", "-m", "initial"])?;
+    git(&["mv", "old_name.txt", "new_name.txt"])?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-G", "--color", "never"]).arg(temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("R "))
+        .stdout(predicate::str::contains("new_name.txt (was old_name.txt)"));
+
+    Ok(())
+}
+
+#[test]
+fn test_paginate_is_skipped_without_terminal() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("file.txt"), "")?;
+
+    // The pager would hang the test if it were started, so point it at something inert.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.env("PAGER", "false").args(["--paginate", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("└── file.txt"));
+
+    Ok(())
+}
+
+#[test]
+fn test_output_file_is_written_without_colors() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let root = temp_dir.path().join("root");
+    fs::create_dir_all(root.join("src"))?;
+    let output_file = temp_dir.path().join("tree.txt");
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.env("CLICOLOR_FORCE", "1").arg("--output-file").arg(&output_file).arg(&root);
+    cmd.assert().success().stdout(predicate::str::is_empty());
+
+    let written = fs::read_to_string(&output_file)?;
+    assert!(written.contains("└── src"));
+    assert!(!written.contains('\x1b'));
+
+    Ok(())
+}
+
+#[test]
+fn test_profile_reports_phases_on_stderr() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("file.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--profile", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("└── file.txt"))
+        .stdout(predicate::str::contains("profile").not())
+        .stderr(predicate::str::contains("lstr: profile:"))
+        .stderr(predicate::str::contains("  walk "))
+        .stderr(predicate::str::contains("  render "))
+        .stderr(predicate::str::contains("  total "));
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_subcommand() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let old = temp_dir.path().join("old");
+    let new = temp_dir.path().join("new");
+    for root in [&old, &new] {
+        fs::create_dir_all(root.join("src"))?;
+        fs::write(root.join("src/common.rs"), "")?;
+    }
+    fs::write(old.join("src/removed.rs"), "")?;
+    fs::write(new.join("added.txt"), "")?;
+    fs::write(old.join("grown.txt"), "a")?;
+    fs::write(new.join("grown.txt"), "abc")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["diff", "-s", "--color", "never"]).arg(&old).arg(&new);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("+ ├── added.txt"))
+        .stdout(predicate::str::contains("~ ├── grown.txt (1 B → 3 B)"))
+        .stdout(predicate::str::contains("  └── src\n      ├── common.rs\n-     └── removed.rs"))
+        .stdout(predicate::str::contains("1 added, 1 removed, 1 changed, 2 unchanged"));
+
+    Ok(())
+}
+
+#[test]
+fn test_mark_empty_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("blank.txt"), "")?;
+    fs::write(temp_dir.path().join("full.txt"), "content")?;
+    fs::create_dir(temp_dir.path().join("hollow"))?;
+    fs::create_dir(temp_dir.path().join("hidden_only"))?;
+    fs::write(temp_dir.path().join("hidden_only/.keep"), "x")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--mark-empty", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("blank.txt (empty)"))
+        .stdout(predicate::str::contains("hollow (empty)"))
+        .stdout(predicate::str::contains("full.txt (empty)").not())
+        .stdout(predicate::str::contains("hidden_only (empty)").not());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_non_utf8_names_are_escaped() -> Result<(), Box<dyn std::error::Error>> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join(OsStr::from_bytes(b"caf\xe9.txt")), "")?;
+    fs::write(temp_dir.path().join(OsStr::from_bytes(b"caf\xea.txt")), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r"caf\xe9.txt"))
+        .stdout(predicate::str::contains(r"caf\xea.txt"));
+
+    Ok(())
+}
+
+#[test]
+fn test_repeat_rejects_invalid_use() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--repeat", "0"]).arg(temp_dir.path());
+    cmd.assert().failure();
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--repeat", "2", "--watch"]).arg(temp_dir.path());
+    cmd.assert().failure().stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
+#[test]
+fn test_fit_is_ignored_when_not_a_terminal() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir_all(temp_dir.path().join("a/b/c"))?;
+    fs::write(temp_dir.path().join("a/b/c/deep.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--fit", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("deep.txt"));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_permissions_flag_highlights_risky_modes() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let open_file = temp_dir.path().join("open.txt");
+    let setuid_file = temp_dir.path().join("setuid.bin");
+    fs::write(&open_file, "")?;
+    fs::write(&setuid_file, "")?;
+    fs::set_permissions(&open_file, fs::Permissions::from_mode(0o666))?;
+    fs::set_permissions(&setuid_file, fs::Permissions::from_mode(0o4755))?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["-p", "--color", "always"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[31m-rw-rw-rw-"))
+        .stdout(predicate::str::contains("\x1b[1;33m-rwsr-xr-x"));
+
+    Ok(())
+}
+
+#[test]
+fn test_default_sort_is_by_name_case_insensitive() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    // Created out of order, so the output can't just follow creation or inode order.
+    for name in ["b.txt", "C.txt", "a.txt", "D.txt"] {
+        fs::write(temp_dir.path().join(name), "")?;
+    }
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--color", "never"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::is_match(r"(?s)a\.txt.*b\.txt.*C\.txt.*D\.txt")?);
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--sort", "none", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("4 files"));
+
+    Ok(())
+}
+
+#[test]
+fn test_no_icons_for() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("main.rs"), "")?;
+    fs::write(temp_dir.path().join("notes.md"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--color", "never", "--icons", "--no-icons-for", ".RS,toml"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("├──   main.rs"))
+        .stdout(predicate::str::contains("\u{e68b}").not())
+        .stdout(predicate::str::contains("notes.md"));
+
+    Ok(())
+}
+
+#[test]
+fn test_icons_for_directories_only() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("src"))?;
+    fs::write(temp_dir.path().join("main.rs"), "")?;
+
+    // Without `=`, the next argument is still the path.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--color", "never", "--icons"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("\u{e68b} main.rs"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--color", "never", "--icons=dirs"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\u{f115} src"))
+        .stdout(predicate::str::contains("── main.rs"));
+
+    Ok(())
+}
+
+#[test]
+fn test_exit_code_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("empty"))?;
+    fs::write(temp_dir.path().join("file.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--exit-code").arg(temp_dir.path().join("empty"));
+    cmd.assert().code(1);
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg(temp_dir.path().join("empty"));
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg("--exit-code").arg(temp_dir.path());
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--exit-code", "--count-by-extension"]).arg(temp_dir.path().join("empty"));
+    cmd.assert().code(1);
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--exit-code", "--count-by-extension"]).arg(temp_dir.path());
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--exit-code", "--output", "tree-json"]).arg(temp_dir.path().join("empty"));
+    cmd.assert().code(1);
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--exit-code", "--output", "tree-json"]).arg(temp_dir.path());
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn test_group_by_extension() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("album"))?;
+    fs::write(temp_dir.path().join("album/b.jpg"), "")?;
+    fs::write(temp_dir.path().join("a.jpg"), "")?;
+    fs::write(temp_dir.path().join("c.png"), "")?;
+    fs::write(temp_dir.path().join("README"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--group-by-extension", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("├──").not())
+        .stdout(predicate::str::is_match(
+            r"(?s)^jpg \(2 files\)\n  a\.jpg\n  album/b\.jpg\n\npng \(1 files\)\n  c\.png\n\n\(none\) \(1 files\)\n  README\n\n3 extensions, 4 files\n$",
+        )?);
+
+    Ok(())
+}
+
+#[test]
+fn test_extension_case_folding() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("a.JPG"), "")?;
+    fs::write(temp_dir.path().join("b.png"), "")?;
+    fs::write(temp_dir.path().join("c.jpg"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--group-by-extension", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::starts_with("jpg (2 files)\n  a.JPG\n  c.jpg\n"));
+
+    // Sorting folds the case of extensions too, while names keep theirs.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--sort", "extension", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::is_match(r"(?s)a\.JPG.*c\.jpg.*b\.png")?);
+
+    Ok(())
+}
+
+#[test]
+fn test_group_by_initial() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("apple.txt"), "")?;
+    fs::write(temp_dir.path().join("Avocado.txt"), "")?;
+    fs::create_dir(temp_dir.path().join("banana"))?;
+    fs::write(temp_dir.path().join("1st.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--group-by-initial", "--color", "never"]).arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::is_match(
+        r"(?s)^A \(2 entries\)\n  apple\.txt\n  Avocado\.txt\n\nB \(1 entries\)\n  banana/\n\n# \(1 entries\)\n  1st\.txt\n\n3 groups, 4 entries\n$",
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn test_sort_case_sensitive_and_alias() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("a.txt"), "")?;
+    fs::write(temp_dir.path().join("B.txt"), "")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.arg(temp_dir.path());
+    cmd.assert().success().stdout(predicate::str::is_match(r"(?s)a\.txt.*B\.txt")?);
+
+    for flag in ["--sort-case-sensitive", "--case-sensitive"] {
+        let mut cmd = Command::cargo_bin("lstr")?;
+        cmd.arg(flag).arg(temp_dir.path());
+        cmd.assert().success().stdout(predicate::str::is_match(r"(?s)B\.txt.*a\.txt")?);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_symlinked_root_shows_real_path() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("target"))?;
+    std::os::unix::fs::symlink("target", temp_dir.path().join("link"))?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--color", "never"]).arg(temp_dir.path().join("link/"));
+    cmd.assert().success().stdout(predicate::str::is_match(r"^\S*link/? -> \S*target\n")?);
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.current_dir(temp_dir.path().join("target")).args(["--real-path", "--color", "never"]);
+    cmd.assert().success().stdout(predicate::str::is_match(r"^\. -> \S*target\n")?);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_canonical_resolves_links() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    let root = fs::canonicalize(temp_dir.path())?;
+    fs::create_dir(root.join("docs"))?;
+    fs::write(root.join("docs/guide.md"), "")?;
+    std::os::unix::fs::symlink("docs/guide.md", root.join("guide"))?;
+    std::os::unix::fs::symlink("missing.md", root.join("broken"))?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.current_dir(root.join("docs")).args(["--canonical", "--color", "never", ".."]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+
+    assert!(output.starts_with(&format!("{}\n", root.display())));
+    assert!(output.contains(&format!("── guide -> {}\n", root.join("docs/guide.md").display())));
+    assert!(output.contains("── broken -> missing.md (unresolved)\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_fields_with_separator() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::create_dir(temp_dir.path().join("src"))?;
+    fs::write(temp_dir.path().join("src/main.rs"), "12345")?;
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.current_dir(temp_dir.path()).args(["--fields", "size,path", "--bytes", "--color", "never"]);
+    cmd.assert().success().stdout("\t./src\n5\t./src/main.rs\n");
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.current_dir(temp_dir.path()).args(["--fields", "name,size", "--separator", "|", "--bytes"]);
+    cmd.assert().success().stdout(predicate::str::contains("main.rs|5\n"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["--fields", "bogus"]).arg(temp_dir.path());
+    cmd.assert().failure().stderr(predicate::str::contains("unknown field"));
+
+    Ok(())
+}
+
+#[test]
+fn test_fields_time_collects_metadata() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempdir()?;
+    fs::write(temp_dir.path().join("fresh.txt"), "")?;
+
+    // No --time flag: asking for the field is enough.
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.current_dir(temp_dir.path()).args(["--fields", "mtime,name", "--color", "never"]);
+    cmd.assert().success().stdout(predicate::str::is_match(r"^(\d+s|now)\tfresh\.txt\n$")?);
+
+    Ok(())
+}
+
+#[test]
+fn test_shell_init() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["shell-init", "bash"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("lcd() {\n"))
+        .stdout(predicate::str::contains("lstr interactive --print-on-exit \"$@\""));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["shell-init", "fish", "--cmd", "pick"]);
+    cmd.assert().success().stdout(predicate::str::starts_with("function pick\n"));
+
+    let mut cmd = Command::cargo_bin("lstr")?;
+    cmd.args(["shell-init", "bash", "--cmd", "x;rm"]);
+    cmd.assert().failure();
+
+    Ok(())
+}